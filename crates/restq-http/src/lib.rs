@@ -11,7 +11,7 @@ pub use restq::{
     },
     parser::select,
     pom::parser::{sym, tag, Parser},
-    space, to_chars, CsvRows, DataValue, Error, StmtData,
+    space, to_chars, CsvRows, DataValue, Dialect, Error, StmtData,
 };
 use std::io::Cursor;
 
@@ -147,15 +147,16 @@ mod tests {
                 },
                 foreign: None,
             }],
+            constraints: vec![],
         };
         let mut table_lookup = TableLookup::new();
         table_lookup.add_table(users_table);
         assert_eq!(
             statement
-                .into_sql_statement(Some(&table_lookup))
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
                 .expect("must not fail")
                 .to_string(),
-            "CREATE TABLE IF NOT EXISTS product (product_id SERIAL PRIMARY KEY NOT NULL, name text NOT NULL, description text NOT NULL, updated timestamp NOT NULL, created_by int NOT NULL REFERENCES users (user_id), is_active boolean NOT NULL)"
+            "CREATE TABLE IF NOT EXISTS product (product_id SERIAL PRIMARY KEY NOT NULL, name text NOT NULL, description text NOT NULL, updated timestamp NOT NULL, created_by int NOT NULL CONSTRAINT product_created_by_fkey REFERENCES users (user_id), is_active boolean NOT NULL)"
         );
     }
 
@@ -187,6 +188,7 @@ mod tests {
                 },
                 foreign: None,
             }],
+            constraints: vec![],
         };
         let users_table = TableDef {
             table: TableName {
@@ -209,13 +211,14 @@ mod tests {
                     column: Some(ColumnName { name: "id".into() }),
                 }),
             }],
+            constraints: vec![],
         };
         let mut table_lookup = TableLookup::new();
         table_lookup.add_table(person_table);
         table_lookup.add_table(users_table);
         assert_eq!(
             statement
-                .into_sql_statement(Some(&table_lookup))
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
                 .unwrap()
                 .to_string(),
             "SELECT name, age, class FROM person JOIN users ON users.person_id = person.id WHERE (age > 42 AND student = true) OR (gender = 'M' AND is_active = true) GROUP BY sum(age), grade, gender HAVING min(age) >= 42 ORDER BY age DESC, height ASC LIMIT 10 OFFSET 10"
@@ -2,16 +2,20 @@ pub mod ddl;
 pub mod dml;
 mod expr;
 mod operator;
+mod params;
 pub mod parser;
+mod set_operation;
 mod table;
 mod value;
 
-use crate::Error;
-pub use ddl::{AlterTable, DropTable, Foreign, TableDef};
+use crate::{DataValue, Dialect, Error};
+pub use ddl::{AlterTable, DropTable, Foreign, TableConstraint, TableDef};
 pub use dml::{BulkDelete, BulkUpdate, Delete, Insert, Update};
-pub use expr::{BinaryOperation, Expr, ExprRename};
+pub use expr::{BinaryOperation, Expr, ExprRename, UnaryOperation};
 pub use operator::Operator;
+use params::Params;
 use serde::{Deserialize, Serialize};
+pub use set_operation::{SetOperation, SetOperator};
 use sql_ast::ast as sql;
 use std::fmt;
 pub use table::{FromTable, JoinType, TableError, TableLookup, TableName};
@@ -20,6 +24,7 @@ pub use value::Value;
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Statement {
     Select(Select),
+    SetOperation(SetOperation),
     Insert(Insert),
     Update(Update),
     BulkUpdate(BulkUpdate),
@@ -28,6 +33,10 @@ pub enum Statement {
     Create(TableDef),
     DropTable(DropTable),
     AlterTable(AlterTable),
+    /// show the definition of the table, ie: `HEAD /product`
+    DescribeTable(TableName),
+    /// show the definition of all the tables, ie: `HEAD /`
+    ListTables,
 }
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
@@ -37,8 +46,20 @@ pub struct Select {
     pub group_by: Option<Vec<Expr>>,
     pub having: Option<Expr>,
     pub projection: Option<Vec<ExprRename>>, // column selection
+    /// only return distinct rows, ie: `person{distinct:city}`
+    pub distinct: bool,
     pub order_by: Option<Vec<Order>>,
     pub range: Option<Range>,
+    /// named selects which can be used as tables in this select,
+    /// ie: `&with=top_product:(GET/product{name}?price=gt.100)`
+    pub with: Option<Vec<Cte>>,
+}
+
+/// a select with a name, which is used as a table
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Cte {
+    pub name: String,
+    pub select: Select,
 }
 
 #[derive(
@@ -61,12 +82,25 @@ pub struct ColumnName {
 pub struct Function {
     pub name: String,
     pub params: Vec<Expr>,
+    /// only aggregate the distinct values, ie: `count(distinct.city)`
+    pub distinct: bool,
+    /// the window of a window function,
+    /// ie: `row_number().over(partition_by=dept,order_by=salary.desc)`
+    pub over: Option<Window>,
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+pub struct Window {
+    pub partition_by: Vec<Expr>,
+    pub order_by: Vec<Order>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub expr: Expr,
     pub direction: Option<Direction>,
+    /// where the nulls are placed, ie: `price.desc.nullslast`
+    pub nulls: Option<Nulls>,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -75,10 +109,17 @@ pub enum Direction {
     Desc,
 }
 
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Range {
     Page(Page),
     Limit(Limit),
+    Keyset(Keyset),
 }
 
 impl Range {
@@ -86,6 +127,7 @@ impl Range {
         match self {
             Range::Page(page) => page.page_size,
             Range::Limit(limit) => limit.limit,
+            Range::Keyset(keyset) => keyset.limit,
         }
     }
 
@@ -93,6 +135,7 @@ impl Range {
         match self {
             Range::Page(page) => Some((page.page - 1) * page.page_size),
             Range::Limit(limit) => limit.offset,
+            Range::Keyset(_) => None,
         }
     }
 }
@@ -109,39 +152,256 @@ pub struct Limit {
     pub offset: Option<i64>,
 }
 
+/// the rows after the cursor in the order of the order_by, where
+/// each value of the cursor is for each of the order_by,
+/// ie: `order_by=created.desc,id.desc&after='2020-01-01T00:00:00',123&limit=50`
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+pub struct Keyset {
+    pub after: Vec<Value>,
+    pub limit: i64,
+}
+
 impl Statement {
+    /// convert into a single statement of the sql ast, this is the same as
+    /// `into_sql_statements` except it is an error when more than one
+    /// statement or no statement is generated, ie: a bulk delete with no rows.
+    ///
+    /// The upsert and returning clauses, the indexes of a table and altering
+    /// a column can not be expressed in the sql ast and are only rendered by
+    /// `into_sql`, `into_multiple_sql` and `into_sql_with_params`
     pub fn into_sql_statement(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Statement, Error> {
+        let mut statements = self.into_sql_statements(table_lookup, dialect)?;
+        match statements.len() {
+            0 => Err(Error::NoStatement),
+            1 => Ok(statements.remove(0)),
+            _ => Err(Error::MoreThanOneStatement),
+        }
+    }
+
+    /// convert into the statements of the sql ast, bulk update, bulk delete
+    /// and alter table can generate multiple statements
+    pub fn into_sql_statements(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::Statement>, Error> {
         match self {
             Statement::Select(select) => {
-                select.into_sql_statement(table_lookup)
+                Ok(vec![select.into_sql_statement(table_lookup, dialect)?])
+            }
+            Statement::SetOperation(set_operation) => {
+                Ok(vec![
+                    set_operation.into_sql_statement(table_lookup, dialect)?
+                ])
             }
             Statement::Insert(insert) => {
-                insert.into_sql_statement(table_lookup)
+                Ok(vec![insert.into_sql_statement(table_lookup, dialect)?])
+            }
+            Statement::Update(update) => {
+                Ok(vec![update.into_sql_statement(dialect)?])
+            }
+            Statement::Delete(delete) => {
+                Ok(vec![delete.into_sql_statement(dialect)?])
             }
-            Statement::Update(update) => update.into_sql_statement(),
-            Statement::Delete(delete) => delete.into_sql_statement(),
-            Statement::BulkUpdate(_update) => todo!(),
-            Statement::BulkDelete(_delete) => todo!(),
             Statement::Create(create) => {
-                Ok(create.into_sql_statement(table_lookup)?)
+                Ok(vec![create.into_sql_statement(table_lookup, dialect)?])
             }
             Statement::DropTable(drop_table) => {
-                Ok(drop_table.into_sql_statement()?)
+                Ok(vec![drop_table.into_sql_statement(dialect)?])
+            }
+            Statement::BulkUpdate(bulk_update) => {
+                bulk_update.into_sql_statements(table_lookup, dialect)
+            }
+            Statement::BulkDelete(bulk_delete) => {
+                bulk_delete.into_sql_statements(table_lookup, dialect)
             }
             Statement::AlterTable(alter_table) => {
-                let mut statements =
-                    alter_table.into_sql_statements(table_lookup)?;
-                if statements.len() == 1 {
-                    Ok(statements.remove(0))
-                } else {
-                    Err(Error::MoreThanOneStatement)
-                }
+                alter_table.into_sql_statements(table_lookup, dialect)
+            }
+            Statement::DescribeTable(_) | Statement::ListTables => {
+                Err(Error::UnsupportedOperation(
+                    "HEAD is answered from the table lookup with `describe`"
+                        .to_string(),
+                ))
+            }
+        }
+    }
+
+    /// convert this statement into a sql text, this includes the clauses
+    /// that can not be expressed in the sql ast such as the upsert,
+    /// the returning, altering a column and creating the indexes
+    pub fn into_sql(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<String, Error> {
+        let mut statements = self.into_multiple_sql(table_lookup, dialect)?;
+        match statements.len() {
+            0 => Err(Error::NoStatement),
+            1 => Ok(statements.remove(0)),
+            _ => Err(Error::MoreThanOneStatement),
+        }
+    }
+
+    /// same as `into_sql`, except create table, bulk update, bulk delete
+    /// and alter table can generate multiple statements
+    pub fn into_multiple_sql(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<String>, Error> {
+        if let Some(statements) = self.ddl_sql(table_lookup, dialect)? {
+            return Ok(statements);
+        }
+        let statements = self.into_sql_statements(table_lookup, dialect)?;
+        let trailing_clause = self.trailing_clause(table_lookup, dialect)?;
+        Ok(statements
+            .into_iter()
+            .map(|statement| {
+                with_trailing_clause(
+                    statement.to_string(),
+                    trailing_clause.clone(),
+                )
+            })
+            .collect())
+    }
+
+    /// the create table with its indexes and the alter table, which
+    /// can not be expressed in the sql ast
+    fn ddl_sql(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Option<Vec<String>>, Error> {
+        match self {
+            Statement::Create(table_def) => {
+                Ok(Some(table_def.into_sql(table_lookup, dialect)?))
+            }
+            Statement::AlterTable(alter_table) => {
+                Ok(Some(alter_table.into_sql(table_lookup, dialect)?))
             }
+            _ => Ok(None),
         }
     }
+
+    /// the upsert and returning clauses which are appended to the sql text
+    fn trailing_clause(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        let clauses = match self {
+            Statement::Insert(insert) => vec![
+                insert.sql_on_conflict(table_lookup, dialect)?,
+                insert.sql_returning(dialect)?,
+            ],
+            Statement::Update(update) => {
+                vec![update.sql_returning(dialect)?]
+            }
+            Statement::Delete(delete) => {
+                vec![delete.sql_returning(dialect)?]
+            }
+            _ => vec![],
+        };
+        let clauses: Vec<String> = clauses.into_iter().flatten().collect();
+        if clauses.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(clauses.join(" ")))
+        }
+    }
+
+    /// answer the HEAD statements from the table lookup, with the table
+    /// definitions in restq syntax, one table definition per line
+    pub fn describe(
+        &self,
+        table_lookup: &TableLookup,
+    ) -> Result<String, Error> {
+        match self {
+            Statement::DescribeTable(table) => {
+                let table_def =
+                    table_lookup.find_table(table).ok_or_else(|| {
+                        TableError::TableNotFound(table.to_string())
+                    })?;
+                Ok(table_def.to_string())
+            }
+            Statement::ListTables => Ok(table_lookup
+                .table_defs()
+                .map(|table_def| table_def.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")),
+            _ => Err(Error::UnsupportedOperation(
+                "only HEAD statements can be described".to_string(),
+            )),
+        }
+    }
+
+    /// convert this statement into a sql text where the literal values
+    /// are replaced with the placeholders of the dialect, `$1`, `$2`,.. in
+    /// postgresql, together with the values to be bound to the placeholders,
+    /// in order.
+    ///
+    /// The table definition from the table lookup, when supplied, is used to
+    /// cast the values into the data type of their corresponding column.
+    pub fn into_sql_with_params(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<(String, Vec<DataValue>), Error> {
+        let mut statements =
+            self.into_multiple_sql_with_params(table_lookup, dialect)?;
+        match statements.len() {
+            0 => Err(Error::NoStatement),
+            1 => Ok(statements.remove(0)),
+            _ => Err(Error::MoreThanOneStatement),
+        }
+    }
+
+    /// same as `into_sql_with_params`, except bulk update, bulk delete
+    /// and alter table can generate multiple statements.
+    pub fn into_multiple_sql_with_params(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<(String, Vec<DataValue>)>, Error> {
+        // ddl statements have no values to be bound
+        if let Some(statements) = self.ddl_sql(table_lookup, dialect)? {
+            return Ok(statements
+                .into_iter()
+                .map(|sql| (sql, vec![]))
+                .collect());
+        }
+
+        let trailing_clause = self.trailing_clause(table_lookup, dialect)?;
+        let statements = self.into_sql_statements(table_lookup, dialect)?;
+
+        statements
+            .into_iter()
+            .map(|mut statement| {
+                let mut params = Params::new(table_lookup, dialect);
+                params.parameterize_statement(&mut statement)?;
+                Ok((
+                    with_trailing_clause(
+                        statement.to_string(),
+                        trailing_clause.clone(),
+                    ),
+                    params.into_values(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// append the clause which is not supported in the sql ast to the sql text
+fn with_trailing_clause(sql: String, clause: Option<String>) -> String {
+    match clause {
+        Some(clause) => format!("{} {}", sql, clause),
+        None => sql,
+    }
 }
 
 impl Into<Statement> for Select {
@@ -171,6 +431,64 @@ impl Select {
         }
     }
 
+    /// the cursor of the page after the last returned row, where the values
+    /// are taken from the columns of the row that matches the order_by.
+    /// Returns None if there is no range, or the order_by is not
+    /// in the returned columns
+    pub fn next_cursor(
+        &self,
+        columns: &[ColumnName],
+        last_row: &[DataValue],
+    ) -> Option<Keyset> {
+        let limit = self.range.as_ref()?.limit();
+        let order_by = self.order_by.as_ref()?;
+        let after = order_by
+            .iter()
+            .map(|order| match &order.expr {
+                Expr::Column(order_column) => self
+                    .cursor_column(columns, order_column)
+                    .and_then(|i| last_row.get(i))
+                    .map(Into::into),
+                _ => None,
+            })
+            .collect::<Option<Vec<Value>>>()?;
+        Some(Keyset { after, limit })
+    }
+
+    /// the position of the order column in the returned columns, matched
+    /// by its exact name, or by the column name alone when it is qualified
+    /// with the table of this select and no other returned column has
+    /// the same name
+    fn cursor_column(
+        &self,
+        columns: &[ColumnName],
+        order_column: &ColumnName,
+    ) -> Option<usize> {
+        if let Some(i) = columns
+            .iter()
+            .position(|column| column.name == order_column.name)
+        {
+            return Some(i);
+        }
+        let table = self
+            .from_table
+            .alias
+            .as_ref()
+            .unwrap_or(&self.from_table.from.name);
+        let name = order_column
+            .name
+            .strip_prefix(table.as_str())?
+            .strip_prefix('.')?;
+        let mut matches = columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| column.name == name);
+        match (matches.next(), matches.next()) {
+            (Some((i, _)), None) => Some(i),
+            _ => None,
+        }
+    }
+
     pub fn add_simple_filter(
         &mut self,
         column: ColumnName,
@@ -190,52 +508,165 @@ impl Select {
     pub fn into_sql_select(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Select, Error> {
         let select = sql::Select {
-            distinct: false,
+            distinct: self.distinct,
             projection: if let Some(projection) = self.projection.as_ref() {
                 projection
                     .iter()
                     .map(|proj| {
-                        if let Some(rename) = &proj.rename {
+                        let expr = proj.expr.into_sql_expr(dialect)?;
+                        Ok(if let Some(rename) = &proj.rename {
                             sql::SelectItem::ExprWithAlias {
-                                expr: Into::into(&proj.expr),
+                                expr,
                                 alias: sql::Ident::new(rename),
                             }
                         } else {
-                            sql::SelectItem::UnnamedExpr(Into::into(&proj.expr))
-                        }
+                            sql::SelectItem::UnnamedExpr(expr)
+                        })
                     })
-                    .collect::<Vec<_>>()
+                    .collect::<Result<Vec<_>, Error>>()?
             } else {
                 vec![sql::SelectItem::Wildcard]
             },
-            from: vec![self.from_table.into_table_with_joins(table_lookup)?],
-            selection: self.filter.as_ref().map(|expr| Into::into(expr)),
+            from: vec![self
+                .from_table
+                .into_table_with_joins(table_lookup, dialect)?],
+            selection: match (
+                self.filter
+                    .as_ref()
+                    .map(|expr| expr.into_sql_expr(dialect))
+                    .transpose()?,
+                self.keyset_predicate(dialect)?,
+            ) {
+                (Some(filter), Some(keyset)) => Some(sql::Expr::BinaryOp {
+                    left: Box::new(sql::Expr::Nested(Box::new(filter))),
+                    op: sql::BinaryOperator::And,
+                    right: Box::new(keyset),
+                }),
+                (filter, keyset) => filter.or(keyset),
+            },
             group_by: match &self.group_by {
-                Some(group_by) => {
-                    group_by.iter().map(|expr| Into::into(expr)).collect()
-                }
+                Some(group_by) => group_by
+                    .iter()
+                    .map(|expr| expr.into_sql_expr(dialect))
+                    .collect::<Result<_, _>>()?,
                 None => vec![],
             },
-            having: self.having.as_ref().map(|expr| Into::into(expr)),
+            having: self
+                .having
+                .as_ref()
+                .map(|expr| expr.into_sql_expr(dialect))
+                .transpose()?,
         };
         Ok(select)
     }
 
+    /// the rows after the keyset cursor, which is a row comparison
+    /// when the order_by are all in the same direction,
+    /// ie: `(created, id) < ('2020-01-01', 123)`,
+    /// otherwise each of the order_by is compared in turn,
+    /// ie: `(created < '2020-01-01' OR (created = '2020-01-01' AND id > 123))`
+    fn keyset_predicate(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<Option<sql::Expr>, Error> {
+        let keyset = match &self.range {
+            Some(Range::Keyset(keyset)) => keyset,
+            _ => return Ok(None),
+        };
+        let order_by = self.order_by.as_deref().unwrap_or_default();
+        if order_by.is_empty() {
+            return Err(Error::InvalidCursor(
+                "after= needs an order_by".to_string(),
+            ));
+        }
+        if order_by.len() != keyset.after.len() {
+            return Err(Error::InvalidCursor(format!(
+                "expecting {} values for the order_by, found {}",
+                order_by.len(),
+                keyset.after.len()
+            )));
+        }
+        let mut exprs = vec![];
+        let mut values = vec![];
+        let mut ops = vec![];
+        for (order, value) in order_by.iter().zip(keyset.after.iter()) {
+            exprs.push(order.expr.into_sql_expr(dialect)?);
+            values.push(sql::Expr::Value(value.into_sql_value(dialect)));
+            ops.push(match order.direction {
+                Some(Direction::Desc) => sql::BinaryOperator::Lt,
+                Some(Direction::Asc) | None => sql::BinaryOperator::Gt,
+            });
+        }
+        let compare =
+            |left: sql::Expr, op, right: sql::Expr| sql::Expr::BinaryOp {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            };
+        let predicate = if ops.iter().all(|op| *op == ops[0]) {
+            if exprs.len() == 1 {
+                compare(exprs.remove(0), ops.remove(0), values.remove(0))
+            } else {
+                compare(
+                    sql::Expr::ValueList(exprs),
+                    ops.remove(0),
+                    sql::Expr::ValueList(values),
+                )
+            }
+        } else {
+            let mut disjunction: Option<sql::Expr> = None;
+            for (i, op) in ops.into_iter().enumerate() {
+                let mut conjunction =
+                    compare(exprs[i].clone(), op, values[i].clone());
+                for j in (0..i).rev() {
+                    conjunction = compare(
+                        compare(
+                            exprs[j].clone(),
+                            sql::BinaryOperator::Eq,
+                            values[j].clone(),
+                        ),
+                        sql::BinaryOperator::And,
+                        conjunction,
+                    );
+                }
+                disjunction = Some(match disjunction {
+                    None => conjunction,
+                    Some(disjunction) => compare(
+                        disjunction,
+                        sql::BinaryOperator::Or,
+                        sql::Expr::Nested(Box::new(conjunction)),
+                    ),
+                });
+            }
+            match disjunction {
+                Some(disjunction) => sql::Expr::Nested(Box::new(disjunction)),
+                None => return Ok(None),
+            }
+        };
+        Ok(Some(predicate))
+    }
+
     pub fn into_sql_query(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Query, Error> {
         let query = sql::Query {
-            ctes: vec![],
+            ctes: match &self.with {
+                Some(with) => with
+                    .iter()
+                    .map(|cte| cte.into_sql_cte(table_lookup, dialect))
+                    .collect::<Result<_, _>>()?,
+                None => vec![],
+            },
             body: sql::SetExpr::Select(Box::new(
-                self.into_sql_select(table_lookup)?,
+                self.into_sql_select(table_lookup, dialect)?,
             )),
             order_by: match &self.order_by {
-                Some(order_by) => {
-                    order_by.iter().map(|expr| Into::into(expr)).collect()
-                }
+                Some(order_by) => into_sql_order_by(order_by, dialect)?,
                 None => vec![],
             },
             limit: self.range.as_ref().map(|range| {
@@ -256,9 +687,10 @@ impl Select {
     pub fn into_sql_statement(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Statement, Error> {
         Ok(sql::Statement::Query(Box::new(
-            self.into_sql_query(table_lookup)?,
+            self.into_sql_query(table_lookup, dialect)?,
         )))
     }
 }
@@ -268,6 +700,9 @@ impl fmt::Display for Select {
         self.from_table.fmt(f)?;
         if let Some(projection) = &self.projection {
             write!(f, "{{")?;
+            if self.distinct {
+                write!(f, "distinct:")?;
+            }
             for (i, exprr) in projection.iter().enumerate() {
                 if i > 0 {
                     write!(f, ",")?;
@@ -309,38 +744,131 @@ impl fmt::Display for Select {
             write!(f, "&")?;
             range.fmt(f)?;
         }
+        if let Some(with) = &self.with {
+            write!(f, "&with=")?;
+            for (i, cte) in with.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                cte.fmt(f)?;
+            }
+        }
 
         Ok(())
     }
 }
 
+impl Cte {
+    pub fn into_sql_cte(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<sql::Cte, Error> {
+        Ok(sql::Cte {
+            alias: sql::TableAlias {
+                name: sql::Ident::new(&self.name),
+                columns: vec![],
+            },
+            query: self.select.into_sql_query(table_lookup, dialect)?,
+        })
+    }
+}
+
+impl fmt::Display for Cte {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:(GET/{})", self.name, self.select)
+    }
+}
+
 impl Default for Direction {
     fn default() -> Self {
         Direction::Asc
     }
 }
 
-impl Into<sql::Function> for &Function {
-    fn into(self) -> sql::Function {
-        sql::Function {
+impl Function {
+    pub fn into_sql_function(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<sql::Function, Error> {
+        Ok(sql::Function {
             name: sql::ObjectName(vec![sql::Ident::new(&self.name)]),
-            args: self.params.iter().map(|expr| Into::into(expr)).collect(),
-            over: None,
-            distinct: false,
-        }
+            args: self
+                .params
+                .iter()
+                .map(|expr| expr.into_sql_expr(dialect))
+                .collect::<Result<_, _>>()?,
+            over: self
+                .over
+                .as_ref()
+                .map(|window| window.into_sql_window_spec(dialect))
+                .transpose()?,
+            distinct: self.distinct,
+        })
+    }
+}
+
+impl Window {
+    pub fn into_sql_window_spec(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<sql::WindowSpec, Error> {
+        Ok(sql::WindowSpec {
+            partition_by: self
+                .partition_by
+                .iter()
+                .map(|expr| expr.into_sql_expr(dialect))
+                .collect::<Result<_, _>>()?,
+            order_by: into_sql_order_by(&self.order_by, dialect)?,
+            window_frame: None,
+        })
     }
 }
 
 impl fmt::Display for Function {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}(", self.name)?;
+        if self.distinct {
+            write!(f, "distinct.")?;
+        }
         for (i, param) in self.params.iter().enumerate() {
             if i > 0 {
                 write!(f, ",")?;
             }
             write!(f, "{}", param)?;
         }
-        write!(f, ")")
+        write!(f, ")")?;
+        if let Some(window) = &self.over {
+            write!(f, ".over({})", window)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.partition_by.is_empty() {
+            write!(f, "partition_by=")?;
+            for (i, expr) in self.partition_by.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                expr.fmt(f)?;
+            }
+        }
+        if !self.order_by.is_empty() {
+            if !self.partition_by.is_empty() {
+                write!(f, ",")?;
+            }
+            write!(f, "order_by=")?;
+            for (i, order) in self.order_by.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                order.fmt(f)?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -356,15 +884,50 @@ impl fmt::Display for ColumnName {
     }
 }
 
-impl Into<sql::OrderByExpr> for &Order {
-    fn into(self) -> sql::OrderByExpr {
-        sql::OrderByExpr {
-            expr: Into::into(&self.expr),
-            asc: self.direction.as_ref().map(|direction| match direction {
-                Direction::Asc => true,
-                Direction::Desc => false,
-            }),
-        }
+/// convert the orders into sql, where an order could be converted into
+/// multiple order by expressions when the nulls ordering is emulated
+pub(crate) fn into_sql_order_by(
+    order_by: &[Order],
+    dialect: &Dialect,
+) -> Result<Vec<sql::OrderByExpr>, Error> {
+    let mut order_by_exprs = vec![];
+    for order in order_by.iter() {
+        order_by_exprs.extend(order.into_sql_order_by_exprs(dialect)?);
+    }
+    Ok(order_by_exprs)
+}
+
+impl Order {
+    /// the sql ast has no nulls ordering, so when the nulls are not already
+    /// placed there by the database it is emulated by ordering first by
+    /// whether the expression is null, ie: `price IS NULL, price DESC`
+    pub fn into_sql_order_by_exprs(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::OrderByExpr>, Error> {
+        let expr = self.expr.into_sql_expr(dialect)?;
+        let asc = self.direction.as_ref().map(|direction| match direction {
+            Direction::Asc => true,
+            Direction::Desc => false,
+        });
+        let nulls_first_by_default =
+            dialect.nulls_are_largest() != asc.unwrap_or(true);
+        let is_null = match &self.nulls {
+            Some(Nulls::First) if !nulls_first_by_default => {
+                sql::Expr::IsNotNull(Box::new(expr.clone()))
+            }
+            Some(Nulls::Last) if nulls_first_by_default => {
+                sql::Expr::IsNull(Box::new(expr.clone()))
+            }
+            _ => return Ok(vec![sql::OrderByExpr { expr, asc }]),
+        };
+        Ok(vec![
+            sql::OrderByExpr {
+                expr: is_null,
+                asc: None,
+            },
+            sql::OrderByExpr { expr, asc },
+        ])
     }
 }
 
@@ -375,10 +938,23 @@ impl fmt::Display for Order {
             write!(f, ".")?;
             direction.fmt(f)?;
         }
+        if let Some(nulls) = &self.nulls {
+            write!(f, ".")?;
+            nulls.fmt(f)?;
+        }
         Ok(())
     }
 }
 
+impl fmt::Display for Nulls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Nulls::First => write!(f, "nullsfirst"),
+            Nulls::Last => write!(f, "nullslast"),
+        }
+    }
+}
+
 impl fmt::Display for Direction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -393,8 +969,42 @@ impl fmt::Display for Range {
         match self {
             Range::Page(page) => page.fmt(f),
             Range::Limit(limit) => limit.fmt(f),
+            Range::Keyset(keyset) => keyset.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Keyset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "after=")?;
+        for (i, value) in self.after.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match value {
+                Value::String(v) => write!(f, "'{}'", escape_cursor_string(v))?,
+                value => value.fmt(f)?,
+            }
+        }
+        write!(f, "&limit={}", self.limit)
+    }
+}
+
+/// escape the string of the cursor, so it is parsed back
+/// as the same single quoted string, ie: `'O\'Brien, Jr.'`
+fn escape_cursor_string(v: &str) -> String {
+    let mut escaped = String::with_capacity(v.len());
+    for c in v.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
         }
     }
+    escaped
 }
 
 impl fmt::Display for Page {
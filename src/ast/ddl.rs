@@ -10,7 +10,7 @@ use crate::{
     },
     data_type::DataType,
     data_value::DataValue,
-    Error,
+    Dialect, Error,
 };
 use chrono::Utc;
 pub use ddl_parser::{alter_table, drop_table, table_def};
@@ -56,6 +56,29 @@ pub enum ColumnAttribute {
 pub struct TableDef {
     pub table: TableName,
     pub columns: Vec<ColumnDef>,
+    #[serde(default)]
+    pub constraints: Vec<TableConstraint>,
+}
+
+/// a constraint over multiple columns of the table
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum TableConstraint {
+    /// `*(order_id,line_no)`
+    PrimaryKey(Vec<ColumnName>),
+    /// `&(order_id,line_no)`
+    Unique(Vec<ColumnName>),
+    /// `(order_id,line_no)(order_line::order_id,line_no)`,
+    /// the referred columns are the primary columns of the foreign table
+    /// when not specified, ie: `(order_id,line_no)(order_line)`
+    ForeignKey {
+        columns: Vec<ColumnName>,
+        foreign_table: TableName,
+        referred_columns: Vec<ColumnName>,
+    },
+    /// `@(category_id,name)`
+    Index(Vec<ColumnName>),
+    /// `&@(category_id,name)`
+    UniqueIndex(Vec<ColumnName>),
 }
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
@@ -82,7 +105,9 @@ pub enum AlterOperation {
 }
 
 impl TableDef {
-    pub fn derive_insert(&self) -> Insert {
+    /// an insert of all the columns of this table, returning the inserted
+    /// row when the database supports it
+    pub fn derive_insert(&self, dialect: &Dialect) -> Insert {
         let columns: Vec<ColumnName> =
             self.columns.iter().map(|c| c.column.clone()).collect();
         Insert {
@@ -95,7 +120,13 @@ impl TableDef {
                     .map(|(i, _c)| i + 1)
                     .collect(),
             ),
-            returning: Some(columns),
+            column_queries: vec![],
+            on_conflict: None,
+            returning: if dialect.supports_returning() {
+                Some(columns)
+            } else {
+                None
+            },
         }
     }
 
@@ -121,42 +152,84 @@ impl TableDef {
             .collect()
     }
 
-    /// return the (local, foreign) pair to this table_name
-    pub(crate) fn get_local_foreign_columns_pair_to_table(
-        &self,
-        table_name: &TableName,
-    ) -> Vec<(&ColumnName, &ColumnName)> {
-        self.columns
-            .iter()
-            .filter_map(|column| {
-                column
-                    .foreign
-                    .as_ref()
-                    .map(|foreign| {
-                        if foreign.table == *table_name {
-                            foreign.column.as_ref().map(|foreign_column| {
-                                (&column.column, foreign_column)
-                            })
-                        } else {
-                            None
+    /// return the (local, foreign) pair to the foreign table,
+    /// a foreign key with no referred column refers to the primary columns
+    /// of the foreign table
+    pub(crate) fn get_local_foreign_columns_pair_to_table<'a>(
+        &'a self,
+        foreign_table_def: &'a TableDef,
+    ) -> Vec<(&'a ColumnName, &'a ColumnName)> {
+        let foreign_primary_columns: Vec<&ColumnName> = foreign_table_def
+            .get_primary_columns()
+            .into_iter()
+            .map(|column_def| &column_def.column)
+            .collect();
+
+        let mut pairs = vec![];
+        for column in self.columns.iter() {
+            if let Some(foreign) = &column.foreign {
+                if foreign.table != foreign_table_def.table {
+                    continue;
+                }
+                match &foreign.column {
+                    Some(foreign_column) => {
+                        pairs.push((&column.column, foreign_column))
+                    }
+                    None => {
+                        if let [foreign_column] =
+                            foreign_primary_columns.as_slice()
+                        {
+                            pairs.push((&column.column, *foreign_column))
                         }
-                    })
-                    .flatten()
-            })
-            .collect()
+                    }
+                }
+            }
+        }
+        for constraint in self.constraints.iter() {
+            if let TableConstraint::ForeignKey {
+                columns,
+                foreign_table,
+                referred_columns,
+            } = constraint
+            {
+                if *foreign_table != foreign_table_def.table {
+                    continue;
+                }
+                if referred_columns.is_empty() {
+                    pairs.extend(
+                        columns
+                            .iter()
+                            .zip(foreign_primary_columns.iter().copied()),
+                    );
+                } else {
+                    pairs.extend(columns.iter().zip(referred_columns.iter()));
+                }
+            }
+        }
+        pairs
     }
 
-    /// get the primary columns of this table
+    /// get the primary columns of this table, the columns of the
+    /// primary key constraint takes precedence over the column attributes
     pub fn get_primary_columns(&self) -> Vec<&ColumnDef> {
-        self.columns
-            .iter()
-            .filter(|column| match &column.attributes {
-                Some(attributes) => attributes
-                    .iter()
-                    .any(|att| *att == ColumnAttribute::Primary),
-                None => false,
-            })
-            .collect()
+        let primary_key =
+            self.constraints
+                .iter()
+                .find_map(|constraint| match constraint {
+                    TableConstraint::PrimaryKey(columns) => Some(columns),
+                    _ => None,
+                });
+        match primary_key {
+            Some(columns) => columns
+                .iter()
+                .filter_map(|column| self.find_column(column))
+                .collect(),
+            None => self
+                .columns
+                .iter()
+                .filter(|column| column.is_primary())
+                .collect(),
+        }
     }
 }
 
@@ -172,32 +245,99 @@ impl Into<Statement> for DropTable {
 }
 
 impl DropTable {
-    pub fn into_sql_statement(&self) -> Result<sql::Statement, Error> {
+    pub fn into_sql_statement(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<sql::Statement, Error> {
         Ok(sql::Statement::Drop {
             object_type: sql::ObjectType::Table,
             if_exists: true,
             names: vec![Into::into(&self.table)],
-            cascade: true,
+            cascade: dialect.supports_cascade(),
         })
     }
 }
 
 impl TableDef {
+    /// the create table only, the indexes are created with `sql_indexes`.
+    /// The primary key is either the primary columns or a primary key
+    /// constraint, having both is an error
     pub fn into_sql_statement(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Statement, Error> {
+        let primary_keys = self
+            .constraints
+            .iter()
+            .filter(|constraint| {
+                matches!(constraint, TableConstraint::PrimaryKey(_))
+            })
+            .count();
+        if primary_keys > 1
+            || (primary_keys == 1
+                && self.columns.iter().any(|c| c.is_primary()))
+        {
+            return Err(TableError::DuplicatePrimaryKey(
+                self.table.to_string(),
+            )
+            .into());
+        }
+        let primary_columns = self.get_primary_columns();
         let mut column_defs = vec![];
         for column in self.columns.iter() {
-            column_defs.push(column.into_sql_column_def(table_lookup)?);
+            let is_primary = primary_columns
+                .iter()
+                .any(|primary| primary.column == column.column);
+            column_defs.push(column.into_sql_column_def(
+                &self.table,
+                is_primary,
+                table_lookup,
+                dialect,
+            )?);
+        }
+        let mut constraints = vec![];
+        // a composite primary key from the column attributes
+        // is declared in the table instead of in each of the columns
+        let primary_columns: Vec<&ColumnDef> =
+            self.columns.iter().filter(|c| c.is_primary()).collect();
+        if primary_columns.len() > 1 {
+            for column_def in column_defs.iter_mut() {
+                column_def.options.retain(|option| {
+                    option.option
+                        != sql::ColumnOption::Unique { is_primary: true }
+                });
+            }
+            constraints.push(sql::TableConstraint::Unique {
+                name: None,
+                columns: primary_columns
+                    .iter()
+                    .map(|column_def| Into::into(&column_def.column))
+                    .collect(),
+                is_primary: true,
+            });
+        }
+        // mysql can not name a column constraint,
+        // they are declared in the table instead
+        if !dialect.supports_named_column_constraints() {
+            for column in self.columns.iter() {
+                constraints
+                    .extend(column.key_constraints(&self.table, table_lookup)?);
+            }
+        }
+        for constraint in self.constraints.iter() {
+            if let Some(constraint) = constraint
+                .into_sql_table_constraint(&self.table, table_lookup)?
+            {
+                constraints.push(constraint);
+            }
         }
-        // TODO for indexes constraint
 
         Ok(sql::Statement::CreateTable {
             if_not_exists: true,
             name: Into::into(&self.table),
             columns: column_defs,
-            constraints: vec![],
+            constraints,
             with_options: vec![],
             external: false,
             file_format: None,
@@ -206,111 +346,841 @@ impl TableDef {
     }
 }
 
+impl TableDef {
+    /// convert into sql texts, the create table followed by the
+    /// create index of the indexed columns, which can not be expressed
+    /// in the sql ast
+    pub fn into_sql(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<String>, Error> {
+        let mut statements =
+            vec![self.into_sql_statement(table_lookup, dialect)?.to_string()];
+        statements.extend(self.sql_indexes(dialect)?);
+        Ok(statements)
+    }
+
+    /// the `CREATE INDEX` of the indexed columns and the index constraints,
+    /// the indexes are named after the table and the columns,
+    /// ie: `product_name_idx`. An index of a column that is not in this
+    /// table is an error
+    pub fn sql_indexes(&self, dialect: &Dialect) -> Result<Vec<String>, Error> {
+        self.indexes()
+            .into_iter()
+            .map(|(columns, unique)| {
+                let column_defs = columns
+                    .iter()
+                    .map(|column| {
+                        self.find_column(column).ok_or_else(|| {
+                            TableError::ColumnNotFound(
+                                self.table.to_string(),
+                                column.to_string(),
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                create_index_sql(&self.table, &column_defs, unique, dialect)
+            })
+            .collect()
+    }
+
+    /// true if this table has indexes
+    pub fn has_indexes(&self) -> bool {
+        !self.indexes().is_empty()
+    }
+
+    /// the columns of each of the indexes and whether it is unique
+    fn indexes(&self) -> Vec<(Vec<ColumnName>, bool)> {
+        let mut indexes: Vec<(Vec<ColumnName>, bool)> = self
+            .columns
+            .iter()
+            .filter_map(|column_def| {
+                column_def
+                    .index()
+                    .map(|unique| (vec![column_def.column.clone()], unique))
+            })
+            .collect();
+        for constraint in self.constraints.iter() {
+            match constraint {
+                TableConstraint::Index(columns) => {
+                    indexes.push((columns.clone(), false))
+                }
+                TableConstraint::UniqueIndex(columns) => {
+                    indexes.push((columns.clone(), true))
+                }
+                _ => (),
+            }
+        }
+        indexes
+    }
+}
+
+/// `CREATE INDEX` of the columns with a name derived from the table and
+/// the columns
+fn create_index_sql(
+    table: &TableName,
+    column_defs: &[&ColumnDef],
+    unique: bool,
+    dialect: &Dialect,
+) -> Result<String, Error> {
+    let table_name: sql::ObjectName = Into::into(table);
+    let columns: Vec<ColumnName> = column_defs
+        .iter()
+        .map(|column_def| column_def.column.clone())
+        .collect();
+    let columns_sql: Vec<String> = column_defs
+        .iter()
+        .map(|column_def| index_column_sql(column_def, unique, dialect))
+        .collect::<Result<_, Error>>()?;
+    Ok(format!(
+        "CREATE {}INDEX {}{} ON {} ({})",
+        if unique { "UNIQUE " } else { "" },
+        if dialect.supports_index_if_not_exists() {
+            "IF NOT EXISTS "
+        } else {
+            ""
+        },
+        constraint_name(table, &columns, "idx"),
+        table_name,
+        columns_sql.join(", ")
+    ))
+}
+
+/// the indexed column, mysql can only index the prefix of a `TEXT` and
+/// `BLOB` column which can not be a unique index since only the prefix
+/// would be unique, and it can not index a `JSON` column
+fn index_column_sql(
+    column_def: &ColumnDef,
+    unique: bool,
+    dialect: &Dialect,
+) -> Result<String, Error> {
+    let ident: sql::Ident = Into::into(&column_def.column);
+    let data_type = column_def.data_type();
+    let unsupported = |index: &str| {
+        Err(Error::UnsupportedOperation(format!(
+            "{} of the {} column {} in {:?}",
+            index, data_type, column_def.column, dialect
+        )))
+    };
+    match data_type.index_prefix_length(dialect) {
+        _ if data_type == DataType::Json && *dialect == Dialect::MySql => {
+            unsupported("index")
+        }
+        Some(_) if unique => unsupported("unique index"),
+        Some(length) => Ok(format!("{}({})", ident, length)),
+        None => Ok(ident.to_string()),
+    }
+}
+
+/// `DROP INDEX` of the index created with `create_index_sql`
+fn drop_index_sql(
+    table: &TableName,
+    columns: &[ColumnName],
+    dialect: &Dialect,
+) -> String {
+    let name = constraint_name(table, columns, "idx");
+    match dialect {
+        Dialect::PostgreSql | Dialect::Sqlite => {
+            format!("DROP INDEX IF EXISTS {}", name)
+        }
+        Dialect::MySql => {
+            let table_name: sql::ObjectName = Into::into(table);
+            format!("DROP INDEX {} ON {}", name, table_name)
+        }
+    }
+}
+
+impl TableConstraint {
+    /// the unique and foreign keys are named after the table and the columns,
+    /// the indexes are created separately with `CREATE INDEX`
+    fn into_sql_table_constraint(
+        &self,
+        table: &TableName,
+        table_lookup: Option<&TableLookup>,
+    ) -> Result<Option<sql::TableConstraint>, Error> {
+        Ok(Some(match self {
+            TableConstraint::PrimaryKey(columns) => {
+                sql::TableConstraint::Unique {
+                    name: None,
+                    columns: columns.iter().map(Into::into).collect(),
+                    is_primary: true,
+                }
+            }
+            TableConstraint::Unique(columns) => sql::TableConstraint::Unique {
+                name: Some(sql::Ident::new(constraint_name(
+                    table, columns, "key",
+                ))),
+                columns: columns.iter().map(Into::into).collect(),
+                is_primary: false,
+            },
+            TableConstraint::ForeignKey {
+                columns,
+                foreign_table,
+                referred_columns,
+            } => {
+                let referred_columns = if referred_columns.is_empty() {
+                    foreign_primary_columns(table_lookup, foreign_table)?
+                } else {
+                    referred_columns.iter().map(Into::into).collect()
+                };
+                if referred_columns.len() != columns.len() {
+                    return Err(TableError::ForeignKeyMismatch(
+                        foreign_table.to_string(),
+                    )
+                    .into());
+                }
+                sql::TableConstraint::ForeignKey {
+                    name: Some(sql::Ident::new(constraint_name(
+                        table, columns, "fkey",
+                    ))),
+                    columns: columns.iter().map(Into::into).collect(),
+                    foreign_table: Into::into(foreign_table),
+                    referred_columns,
+                }
+            }
+            TableConstraint::Index(_) | TableConstraint::UniqueIndex(_) => {
+                return Ok(None)
+            }
+        }))
+    }
+}
+
+/// the primary columns of the foreign table from the table lookup
+fn foreign_primary_columns(
+    table_lookup: Option<&TableLookup>,
+    foreign_table: &TableName,
+) -> Result<Vec<sql::Ident>, Error> {
+    let table_lookup = table_lookup.ok_or(TableError::NoSuppliedTableLookup)?;
+    let foreign_table_def = table_lookup
+        .get_table_def(&foreign_table.name)
+        .ok_or_else(|| TableError::TableNotFound(foreign_table.to_string()))?;
+    Ok(foreign_table_def
+        .get_primary_columns()
+        .into_iter()
+        .map(|column_def| Into::into(&column_def.column))
+        .collect())
+}
+
 impl AlterTable {
     pub fn into_sql_statements(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::Statement>, Error> {
+        let mut statements = vec![];
+        for operation in self.alter_operations.iter() {
+            statements.extend(self.operation_statements(
+                operation,
+                table_lookup,
+                dialect,
+            )?);
+        }
+        Ok(statements)
+    }
+
+    /// the statements of the operation, in mysql the keys of an added column
+    /// are added in separate statements since it can not name a column
+    /// constraint
+    fn operation_statements(
+        &self,
+        operation: &AlterOperation,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<Vec<sql::Statement>, Error> {
+        let alter = |operation| sql::Statement::AlterTable {
+            name: Into::into(&self.table),
+            operation,
+        };
+        let mut statements = vec![alter(operation.into_sql_alter_operation(
+            &self.table,
+            table_lookup,
+            dialect,
+        )?)];
+        if let AlterOperation::AddColumn(column_def) = operation {
+            if !dialect.supports_named_column_constraints() {
+                for constraint in
+                    column_def.key_constraints(&self.table, table_lookup)?
+                {
+                    statements.push(alter(
+                        sql::AlterTableOperation::AddConstraint(constraint),
+                    ));
+                }
+            }
+        }
+        Ok(statements)
+    }
+
+    /// convert into sql texts, this includes altering a column
+    /// which can not be expressed in the sql ast
+    pub fn into_sql(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<String>, Error> {
         let mut statements = vec![];
         for operation in self.alter_operations.iter() {
-            statements.push(sql::Statement::AlterTable {
-                name: Into::into(&self.table),
-                operation: operation.into_sql_alter_operation(table_lookup)?,
+            match operation {
+                AlterOperation::AlterColumn(column, column_def) => statements
+                    .extend(self.alter_column_sql(
+                        column,
+                        column_def,
+                        table_lookup,
+                        dialect,
+                    )?),
+                AlterOperation::DropColumn(column) => {
+                    statements.push(self.drop_column_sql(column, dialect))
+                }
+                _ => statements.extend(
+                    self.operation_statements(
+                        operation,
+                        table_lookup,
+                        dialect,
+                    )?
+                    .iter()
+                    .map(ToString::to_string),
+                ),
+            }
+            if let AlterOperation::AddColumn(column_def) = operation {
+                statements.extend(self.add_column_index(column_def, dialect)?);
+            }
+        }
+        Ok(statements)
+    }
+
+    /// the drop column without the spaces that the sql ast leaves
+    /// in place of the `IF EXISTS` and `CASCADE` which are only
+    /// supported in postgresql
+    fn drop_column_sql(
+        &self,
+        column: &ColumnName,
+        dialect: &Dialect,
+    ) -> String {
+        let table_name: sql::ObjectName = Into::into(&self.table);
+        let column: sql::Ident = Into::into(column);
+        format!(
+            "ALTER TABLE {} DROP COLUMN {}{}{}",
+            table_name,
+            if dialect.supports_drop_column_if_exists() {
+                "IF EXISTS "
+            } else {
+                ""
+            },
+            column,
+            if dialect.supports_cascade() {
+                " CASCADE"
+            } else {
+                ""
+            }
+        )
+    }
+
+    /// the `CREATE INDEX` of the added columns with `@`, which are not
+    /// included in `into_sql_statements`
+    pub fn sql_indexes(&self, dialect: &Dialect) -> Result<Vec<String>, Error> {
+        self.alter_operations
+            .iter()
+            .filter_map(|operation| match operation {
+                AlterOperation::AddColumn(column_def) => {
+                    self.add_column_index(column_def, dialect).transpose()
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn add_column_index(
+        &self,
+        column_def: &ColumnDef,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        column_def
+            .index()
+            .map(|unique| {
+                create_index_sql(&self.table, &[column_def], unique, dialect)
+            })
+            .transpose()
+    }
+
+    /// the alterations of the column from its current definition in the
+    /// table lookup into the new column definition. The constraints are
+    /// named explicitly when the table is created, the same as the default
+    /// names given by postgresql, ie: `product_pkey`, `product_name_key`,
+    /// `product_created_by_fkey` and the indexes are named `product_name_idx`.
+    /// The keys are renamed together with the column.
+    fn alter_column_sql(
+        &self,
+        column: &ColumnName,
+        column_def: &ColumnDef,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<String>, Error> {
+        let table_def = table_lookup
+            .ok_or(TableError::NoSuppliedTableLookup)?
+            .find_table(&self.table)
+            .ok_or_else(|| TableError::TableNotFound(self.table.to_string()))?;
+        let old = table_def.find_column(column).ok_or_else(|| {
+            TableError::ColumnNotFound(
+                self.table.to_string(),
+                column.to_string(),
+            )
+        })?;
+        let new = column_def;
+        let table: sql::ObjectName = Into::into(&self.table);
+        let old_name: sql::Ident = Into::into(&old.column);
+        let new_name: sql::Ident = Into::into(&new.column);
+        let alter =
+            |operation: String| format!("ALTER TABLE {} {}", table, operation);
+        let unsupported = |change: &str| {
+            Error::UnsupportedOperation(format!(
+                "altering the {} of a column in {:?}",
+                change, dialect
+            ))
+        };
+        let add_constraint = |constraint: sql::TableConstraint| {
+            sql::Statement::AlterTable {
+                name: table.clone(),
+                operation: sql::AlterTableOperation::AddConstraint(constraint),
+            }
+            .to_string()
+        };
+        let primary_key = format!("{}_pkey", self.table);
+        let unique_key = |column: &ColumnName| {
+            constraint_name(&self.table, std::slice::from_ref(column), "key")
+        };
+        let foreign_key = |column: &ColumnName| {
+            constraint_name(&self.table, std::slice::from_ref(column), "fkey")
+        };
+        let renamed = old.column != new.column;
+        // mysql can not rename a foreign key, it is recreated instead
+        let recreate_foreign = old.foreign != new.foreign
+            || (renamed && *dialect == Dialect::MySql);
+
+        let mut statements = vec![];
+
+        // the removed or changed constraints are dropped before the column
+        // is renamed, since they are named after the old column name
+        if old.is_primary() && !new.is_primary() {
+            statements.push(match dialect {
+                Dialect::PostgreSql => {
+                    alter(format!("DROP CONSTRAINT {}", primary_key))
+                }
+                Dialect::MySql => alter("DROP PRIMARY KEY".to_string()),
+                Dialect::Sqlite => return Err(unsupported("primary key")),
+            });
+        }
+        if old.is_unique_constraint() && !new.is_unique_constraint() {
+            statements.push(match dialect {
+                Dialect::PostgreSql => alter(format!(
+                    "DROP CONSTRAINT {}",
+                    unique_key(&old.column)
+                )),
+                Dialect::MySql => {
+                    alter(format!("DROP INDEX {}", unique_key(&old.column)))
+                }
+                Dialect::Sqlite => return Err(unsupported("unique key")),
             });
         }
+        // sqlite can not rename an index, it is recreated instead
+        let recreate_index = old.index() != new.index()
+            || (renamed && *dialect == Dialect::Sqlite);
+        if old.index().is_some() && recreate_index {
+            statements.push(drop_index_sql(
+                &self.table,
+                std::slice::from_ref(&old.column),
+                dialect,
+            ));
+        }
+        if old.foreign.is_some() && recreate_foreign {
+            statements.push(match dialect {
+                Dialect::PostgreSql => alter(format!(
+                    "DROP CONSTRAINT {}",
+                    foreign_key(&old.column)
+                )),
+                Dialect::MySql => alter(format!(
+                    "DROP FOREIGN KEY {}",
+                    foreign_key(&old.column)
+                )),
+                Dialect::Sqlite => return Err(unsupported("foreign key")),
+            });
+        }
+
+        if renamed {
+            statements.push(alter(format!(
+                "RENAME COLUMN {} TO {}",
+                old_name, new_name
+            )));
+            // sqlite can not alter the constraints, so they are not renamed
+            if old.is_unique_constraint()
+                && new.is_unique_constraint()
+                && *dialect != Dialect::Sqlite
+            {
+                let old_key = unique_key(&old.column);
+                let new_key = unique_key(&new.column);
+                statements.push(match dialect {
+                    Dialect::MySql => alter(format!(
+                        "RENAME INDEX {} TO {}",
+                        old_key, new_key
+                    )),
+                    _ => alter(format!(
+                        "RENAME CONSTRAINT {} TO {}",
+                        old_key, new_key
+                    )),
+                });
+            }
+            if old.foreign.is_some()
+                && !recreate_foreign
+                && *dialect != Dialect::Sqlite
+            {
+                statements.push(alter(format!(
+                    "RENAME CONSTRAINT {} TO {}",
+                    foreign_key(&old.column),
+                    foreign_key(&new.column)
+                )));
+            }
+            if old.index().is_some() && !recreate_index {
+                let old_index = constraint_name(
+                    &self.table,
+                    std::slice::from_ref(&old.column),
+                    "idx",
+                );
+                let new_index = constraint_name(
+                    &self.table,
+                    std::slice::from_ref(&new.column),
+                    "idx",
+                );
+                statements.push(match dialect {
+                    Dialect::MySql => alter(format!(
+                        "RENAME INDEX {} TO {}",
+                        old_index, new_index
+                    )),
+                    _ => format!(
+                        "ALTER INDEX {} RENAME TO {}",
+                        old_index, new_index
+                    ),
+                });
+            }
+        }
+
+        let type_changed = old.data_type() != new.data_type();
+        let nullability_changed =
+            old.data_type_def.is_optional != new.data_type_def.is_optional;
+        let default_changed =
+            old.data_type_def.default != new.data_type_def.default;
+        match dialect {
+            Dialect::PostgreSql => {
+                if type_changed {
+                    // serial is not a real type, it can only be used
+                    // when creating the column
+                    if new.data_type().is_autogenerate() {
+                        return Err(unsupported(
+                            "data type into an autoincrement",
+                        ));
+                    }
+                    let data_type = new.data_type().into_sql_data_type(dialect);
+                    statements.push(alter(format!(
+                        "ALTER COLUMN {0} TYPE {1} USING CAST({0} AS {1})",
+                        new_name, data_type
+                    )));
+                }
+                if nullability_changed {
+                    statements.push(alter(format!(
+                        "ALTER COLUMN {} {} NOT NULL",
+                        new_name,
+                        if new.data_type_def.is_optional {
+                            "DROP"
+                        } else {
+                            "SET"
+                        }
+                    )));
+                }
+                if default_changed {
+                    statements.push(match &new.data_type_def.default {
+                        Some(default) => alter(format!(
+                            "ALTER COLUMN {} SET DEFAULT {}",
+                            new_name,
+                            default.into_sql_expr(dialect)?
+                        )),
+                        None => alter(format!(
+                            "ALTER COLUMN {} DROP DEFAULT",
+                            new_name
+                        )),
+                    });
+                }
+            }
+            // mysql redefines the column with its type, nullability and default
+            Dialect::MySql => {
+                if type_changed || nullability_changed || default_changed {
+                    let column_def = sql::ColumnDef {
+                        name: new_name.clone(),
+                        data_type: new.sql_data_type(new.is_primary(), dialect),
+                        collation: None,
+                        options: new
+                            .data_type_def
+                            .into_sql_column_options(table_lookup, dialect)?
+                            .into_iter()
+                            .map(|option| sql::ColumnOptionDef {
+                                name: None,
+                                option,
+                            })
+                            .collect(),
+                    };
+                    statements
+                        .push(alter(format!("MODIFY COLUMN {}", column_def)));
+                }
+            }
+            Dialect::Sqlite => {
+                if type_changed || nullability_changed || default_changed {
+                    return Err(unsupported(
+                        "data type, nullability or default",
+                    ));
+                }
+            }
+        }
+
+        if !old.is_primary() && new.is_primary() {
+            if *dialect == Dialect::Sqlite {
+                return Err(unsupported("primary key"));
+            }
+            statements.push(add_constraint(sql::TableConstraint::Unique {
+                name: Some(sql::Ident::new(&primary_key)),
+                columns: vec![new_name.clone()],
+                is_primary: true,
+            }));
+        }
+        if !old.is_unique_constraint() {
+            if let Some(constraint) = new.unique_key_constraint(&self.table) {
+                if *dialect == Dialect::Sqlite {
+                    return Err(unsupported("unique key"));
+                }
+                statements.push(add_constraint(constraint));
+            }
+        }
+        if let Some(unique) = new.index() {
+            if recreate_index {
+                statements.push(create_index_sql(
+                    &self.table,
+                    &[new],
+                    unique,
+                    dialect,
+                )?);
+            }
+        }
+        if recreate_foreign {
+            if let Some(constraint) =
+                new.foreign_key_constraint(&self.table, table_lookup)?
+            {
+                if *dialect == Dialect::Sqlite {
+                    return Err(unsupported("foreign key"));
+                }
+                statements.push(add_constraint(constraint));
+            }
+        }
         Ok(statements)
     }
 }
 
+/// the deterministic name of a constraint or index of the columns,
+/// ie: `product_name_key`
+fn constraint_name(
+    table: &TableName,
+    columns: &[ColumnName],
+    suffix: &str,
+) -> String {
+    let columns: Vec<&str> =
+        columns.iter().map(|column| column.name.as_str()).collect();
+    format!("{}_{}_{}", table.name, columns.join("_"), suffix)
+}
+
 impl AlterOperation {
     fn into_sql_alter_operation(
         &self,
+        table: &TableName,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::AlterTableOperation, Error> {
         match self {
             AlterOperation::AddColumn(column_def) => {
                 Ok(sql::AlterTableOperation::AddColumn(
-                    column_def.into_sql_column_def(table_lookup)?,
+                    column_def.into_sql_column_def(
+                        table,
+                        column_def.is_primary(),
+                        table_lookup,
+                        dialect,
+                    )?,
                 ))
             }
             AlterOperation::DropColumn(column) => {
                 Ok(sql::AlterTableOperation::DropColumn {
                     column: Into::into(column),
-                    if_exists: true,
-                    cascade: true,
+                    if_exists: dialect.supports_drop_column_if_exists(),
+                    cascade: dialect.supports_cascade(),
                 })
             }
-            // get the old column definition
-            // from the table_lookup and see
-            // if it needs rename, add to index, add to unique,
-            // drop the index, etc.
-            AlterOperation::AlterColumn(_column, _column_def) => todo!(),
+            // the alterations of the column is diffed with the current
+            // column definition, which generates several statements
+            // that can not be expressed in the sql ast
+            AlterOperation::AlterColumn(_, _) => {
+                Err(Error::UnsupportedOperation(
+                    "alter column can not be expressed in the sql ast, use `AlterTable::into_sql`"
+                        .to_string(),
+                ))
+            }
         }
     }
 }
 
 impl ColumnDef {
+    /// the unique and foreign keys are named after the table and the column,
+    /// in mysql which can not name a column constraint, they are declared
+    /// in the table with `key_constraints` instead
     fn into_sql_column_options(
         &self,
+        table: &TableName,
         table_lookup: Option<&TableLookup>,
-    ) -> Result<Vec<sql::ColumnOption>, TableError> {
-        let mut att_column_options = match &self.attributes {
-            Some(attributes) => attributes
-                .iter()
-                .filter_map(|att| att.into_sql_column_option())
-                .collect(),
-            None => vec![],
-        };
-        att_column_options
-            .extend(self.data_type_def.into_sql_column_options(table_lookup));
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::ColumnOptionDef>, Error> {
+        let named_keys = dialect.supports_named_column_constraints();
+        let mut options = vec![];
+        if self.is_primary() {
+            options.push(sql::ColumnOptionDef {
+                name: None,
+                option: sql::ColumnOption::Unique { is_primary: true },
+            });
+        }
+        if let (true, Some(sql::TableConstraint::Unique { name, .. })) =
+            (named_keys, self.unique_key_constraint(table))
+        {
+            options.push(sql::ColumnOptionDef {
+                name,
+                option: sql::ColumnOption::Unique { is_primary: false },
+            });
+        }
+        options.extend(
+            self.data_type_def
+                .into_sql_column_options(table_lookup, dialect)?
+                .into_iter()
+                .map(|option| sql::ColumnOptionDef { name: None, option }),
+        );
+        if let (
+            true,
+            Some(sql::TableConstraint::ForeignKey {
+                name,
+                foreign_table,
+                referred_columns,
+                ..
+            }),
+        ) = (
+            named_keys,
+            self.foreign_key_constraint(table, table_lookup)?,
+        ) {
+            options.push(sql::ColumnOptionDef {
+                name,
+                option: sql::ColumnOption::ForeignKey {
+                    foreign_table,
+                    referred_columns,
+                },
+            });
+        }
+        Ok(options)
+    }
 
-        if let Some(foreign) = &self.foreign {
-            match table_lookup {
-                None => return Err(TableError::NoSuppliedTableLookup),
-                Some(table_lookup) => {
-                    let foreign_table_def =
-                        table_lookup.get_table_def(&foreign.table.name);
-
-                    match foreign_table_def {
-                        None => {
-                            return Err(TableError::TableNotFound(
-                                foreign.table.to_string(),
-                            ));
-                        }
-                        Some(foreign_table_def) => {
-                            let pk = foreign_table_def.get_primary_columns();
-                            //TODO: supporting only 1 primary key for now.
-                            assert_eq!(pk.len(), 1);
-                            let fk_column_options =
-                                sql::ColumnOption::ForeignKey {
-                                    foreign_table: Into::into(
-                                        &foreign_table_def.table,
-                                    ),
-                                    referred_columns: vec![Into::into(
-                                        &pk[0].column,
-                                    )],
-                                };
-                            att_column_options.push(fk_column_options);
-                        }
-                    }
-                }
-            }
+    /// the unique key of this column, ie: `product_name_key`,
+    /// a unique index is created instead when the column is also indexed
+    fn unique_key_constraint(
+        &self,
+        table: &TableName,
+    ) -> Option<sql::TableConstraint> {
+        if self.is_unique_constraint() {
+            Some(sql::TableConstraint::Unique {
+                name: Some(sql::Ident::new(constraint_name(
+                    table,
+                    std::slice::from_ref(&self.column),
+                    "key",
+                ))),
+                columns: vec![Into::into(&self.column)],
+                is_primary: false,
+            })
+        } else {
+            None
         }
+    }
 
-        Ok(att_column_options)
+    /// the foreign key of this column, ie: `product_created_by_fkey`
+    fn foreign_key_constraint(
+        &self,
+        table: &TableName,
+        table_lookup: Option<&TableLookup>,
+    ) -> Result<Option<sql::TableConstraint>, Error> {
+        let foreign = match &self.foreign {
+            Some(foreign) => foreign,
+            None => return Ok(None),
+        };
+        let referred_columns = match &foreign.column {
+            Some(column) => vec![Into::into(column)],
+            None => foreign_primary_columns(table_lookup, &foreign.table)?,
+        };
+        // a column can only refer to a single primary column,
+        // use a foreign key constraint for composite primary keys
+        if referred_columns.len() != 1 {
+            return Err(TableError::ForeignKeyMismatch(
+                foreign.table.to_string(),
+            )
+            .into());
+        }
+        Ok(Some(sql::TableConstraint::ForeignKey {
+            name: Some(sql::Ident::new(constraint_name(
+                table,
+                std::slice::from_ref(&self.column),
+                "fkey",
+            ))),
+            columns: vec![Into::into(&self.column)],
+            foreign_table: Into::into(&foreign.table),
+            referred_columns,
+        }))
     }
 
-    fn into_sql_column_options_def(
+    /// the unique and foreign keys of this column as table constraints
+    fn key_constraints(
         &self,
+        table: &TableName,
         table_lookup: Option<&TableLookup>,
-    ) -> Result<Vec<sql::ColumnOptionDef>, TableError> {
+    ) -> Result<Vec<sql::TableConstraint>, Error> {
         Ok(self
-            .into_sql_column_options(table_lookup)?
+            .unique_key_constraint(table)
             .into_iter()
-            .map(|option| sql::ColumnOptionDef { name: None, option })
+            .chain(self.foreign_key_constraint(table, table_lookup)?)
             .collect())
     }
 
+    /// true if the column has this attribute
+    pub fn has_attribute(&self, attribute: &ColumnAttribute) -> bool {
+        self.attributes
+            .as_ref()
+            .map(|attributes| attributes.contains(attribute))
+            .unwrap_or(false)
+    }
+
+    /// true if the column has an index, ie: `@name:text`
+    pub fn is_indexed(&self) -> bool {
+        self.has_attribute(&ColumnAttribute::Index)
+    }
+
+    /// the index of this column, the bool is true for a unique index,
+    /// ie: `&@name:text`
+    fn index(&self) -> Option<bool> {
+        if self.is_indexed() {
+            Some(self.has_attribute(&ColumnAttribute::Unique))
+        } else {
+            None
+        }
+    }
+
+    /// true if the column has a unique constraint which is not an index
+    fn is_unique_constraint(&self) -> bool {
+        self.has_attribute(&ColumnAttribute::Unique) && !self.is_indexed()
+    }
+
     pub fn is_primary(&self) -> bool {
         if let Some(attributes) = &self.attributes {
             attributes
@@ -341,20 +1211,24 @@ impl DataTypeDef {
     fn into_sql_column_options(
         &self,
         _table_lookup: Option<&TableLookup>,
-    ) -> Vec<sql::ColumnOption> {
-        vec![
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::ColumnOption>, Error> {
+        Ok(vec![
             if !self.is_optional {
                 Some(sql::ColumnOption::NotNull)
             } else {
                 None
             },
-            self.default
-                .as_ref()
-                .map(|default| sql::ColumnOption::Default(Into::into(default))),
+            match &self.default {
+                Some(default) => Some(sql::ColumnOption::Default(
+                    default.into_sql_expr(dialect)?,
+                )),
+                None => None,
+            },
         ]
         .into_iter()
-        .filter_map(|v| v)
-        .collect()
+        .flatten()
+        .collect())
     }
 }
 
@@ -384,16 +1258,36 @@ impl fmt::Display for Foreign {
 impl ColumnDef {
     fn into_sql_column_def(
         &self,
+        table: &TableName,
+        is_primary: bool,
         table_lookup: Option<&TableLookup>,
-    ) -> Result<sql::ColumnDef, TableError> {
+        dialect: &Dialect,
+    ) -> Result<sql::ColumnDef, Error> {
         Ok(sql::ColumnDef {
             name: Into::into(&self.column),
-            data_type: Into::into(&self.data_type_def.data_type),
+            data_type: self.sql_data_type(is_primary, dialect),
             collation: None,
-            options: self.into_sql_column_options_def(table_lookup)?,
+            options: self.into_sql_column_options(
+                table,
+                table_lookup,
+                dialect,
+            )?,
         })
     }
 
+    /// only a serial primary column is auto incremented in mysql
+    fn sql_data_type(
+        &self,
+        is_primary: bool,
+        dialect: &Dialect,
+    ) -> sql::DataType {
+        if is_primary {
+            self.data_type().primary_sql_data_type(dialect)
+        } else {
+            self.data_type().into_sql_data_type(dialect)
+        }
+    }
+
     /// create a data_value from this ColumnDef
     pub fn default_value(&self) -> DataValue {
         match &self.data_type_def.default {
@@ -410,6 +1304,56 @@ impl ColumnDef {
     }
 }
 
+impl fmt::Display for TableConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_columns = |f: &mut fmt::Formatter, columns: &[ColumnName]| {
+            for (i, column) in columns.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                column.fmt(f)?;
+            }
+            Ok(())
+        };
+        match self {
+            TableConstraint::PrimaryKey(columns) => {
+                write!(f, "*(")?;
+                write_columns(f, columns)?;
+                write!(f, ")")
+            }
+            TableConstraint::Unique(columns) => {
+                write!(f, "&(")?;
+                write_columns(f, columns)?;
+                write!(f, ")")
+            }
+            TableConstraint::ForeignKey {
+                columns,
+                foreign_table,
+                referred_columns,
+            } => {
+                write!(f, "(")?;
+                write_columns(f, columns)?;
+                write!(f, ")({}", foreign_table)?;
+                if !referred_columns.is_empty() {
+                    write!(f, "::")?;
+                    write_columns(f, referred_columns)?;
+                }
+                write!(f, ")")
+            }
+            TableConstraint::Index(columns) => {
+                write!(f, "@(")?;
+                write_columns(f, columns)?;
+                write!(f, ")")
+            }
+            TableConstraint::UniqueIndex(columns) => {
+                write!(f, "&@(")?;
+                write_columns(f, columns)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 impl fmt::Display for ColumnDef {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(attrs) = &self.attributes {
@@ -427,21 +1371,6 @@ impl fmt::Display for ColumnDef {
     }
 }
 
-impl ColumnAttribute {
-    fn into_sql_column_option(&self) -> Option<sql::ColumnOption> {
-        match self {
-            ColumnAttribute::Primary => {
-                Some(sql::ColumnOption::Unique { is_primary: true })
-            }
-
-            ColumnAttribute::Unique => {
-                Some(sql::ColumnOption::Unique { is_primary: false })
-            }
-            ColumnAttribute::Index => None,
-        }
-    }
-}
-
 impl fmt::Display for ColumnAttribute {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -461,12 +1390,16 @@ impl fmt::Display for DefaultValue {
     }
 }
 
-impl Into<sql::Expr> for &DefaultValue {
-    fn into(self) -> sql::Expr {
-        match self {
-            DefaultValue::DataValue(v) => sql::Expr::Value(v.into()),
-            DefaultValue::Function(v) => sql::Expr::Function(v.into()),
-        }
+impl DefaultValue {
+    fn into_sql_expr(&self, dialect: &Dialect) -> Result<sql::Expr, Error> {
+        Ok(match self {
+            DefaultValue::DataValue(v) => {
+                sql::Expr::Value(v.into_sql_value(dialect))
+            }
+            DefaultValue::Function(v) => {
+                sql::Expr::Function(v.into_sql_function(dialect)?)
+            }
+        })
     }
 }
 
@@ -503,7 +1436,10 @@ mod tests {
         );
         let statement: Statement = Into::into(ret);
         assert_eq!(
-            statement.into_sql_statement(None).unwrap().to_string(),
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
             "DROP TABLE IF EXISTS product CASCADE"
         );
     }
@@ -569,7 +1505,8 @@ mod tests {
         let ret = alter_table().parse(&input).expect("must be parsed");
         println!("{:#?}", ret);
 
-        let statements = ret.into_sql_statements(None).unwrap();
+        let statements =
+            ret.into_sql_statements(None, &Dialect::PostgreSql).unwrap();
         assert_eq!(statements.len(), 2);
         assert_eq!(
             statements[0].to_string(),
@@ -579,6 +1516,465 @@ mod tests {
             statements[1].to_string(),
             "ALTER TABLE product ADD COLUMN discount float DEFAULT 0.1"
         );
+        let drop_column =
+            |dialect| ret.into_sql(None, dialect).unwrap().remove(0);
+        assert_eq!(
+            drop_column(&Dialect::PostgreSql),
+            "ALTER TABLE product DROP COLUMN IF EXISTS description CASCADE"
+        );
+        assert_eq!(
+            drop_column(&Dialect::Sqlite),
+            "ALTER TABLE product DROP COLUMN description"
+        );
+        assert_eq!(
+            drop_column(&Dialect::MySql),
+            "ALTER TABLE product DROP COLUMN description"
+        );
+        let statement = crate::ast::Statement::AlterTable(ret);
+        assert_eq!(
+            statement
+                .into_sql_statements(None, &Dialect::PostgreSql)
+                .unwrap(),
+            statements
+        );
+        assert!(matches!(
+            statement.into_sql_statement(None, &Dialect::PostgreSql),
+            Err(Error::MoreThanOneStatement)
+        ));
+    }
+
+    #[test]
+    fn alter_column_is_diffed() {
+        let mut table_lookup = TableLookup::new();
+        for input in &[
+            "users{*user_id:i32}",
+            "vendor{*vendor_id:i32}",
+            "product{*product_id:i32,name:text,@code:text,price:f64?,is_active:bool(true),created_by(users):i32}",
+        ] {
+            let input = to_chars(input);
+            table_lookup.add_table(table_def().parse(&input).unwrap());
+        }
+        let input = to_chars(
+            "product{name=&title:text?,code=code:text,price=price:u32(0),is_active=is_active:bool,created_by=created_by(vendor):i32}",
+        );
+        let alter_product =
+            alter_table().parse(&input).expect("must be parsed");
+        assert!(alter_product
+            .into_sql_statements(Some(&table_lookup), &Dialect::PostgreSql)
+            .is_err());
+        let sql = |dialect| {
+            alter_product
+                .into_sql(Some(&table_lookup), dialect)
+                .map(|statements| statements.join(";\n"))
+        };
+        assert_eq!(
+            sql(&Dialect::PostgreSql).unwrap(),
+            "ALTER TABLE product RENAME COLUMN name TO title;\n\
+            ALTER TABLE product ALTER COLUMN title DROP NOT NULL;\n\
+            ALTER TABLE product ADD CONSTRAINT product_title_key UNIQUE (title);\n\
+            DROP INDEX IF EXISTS product_code_idx;\n\
+            ALTER TABLE product ALTER COLUMN price TYPE int USING CAST(price AS int);\n\
+            ALTER TABLE product ALTER COLUMN price SET NOT NULL;\n\
+            ALTER TABLE product ALTER COLUMN price SET DEFAULT 0;\n\
+            ALTER TABLE product ALTER COLUMN is_active DROP DEFAULT;\n\
+            ALTER TABLE product DROP CONSTRAINT product_created_by_fkey;\n\
+            ALTER TABLE product ADD CONSTRAINT product_created_by_fkey FOREIGN KEY (created_by) REFERENCES vendor(vendor_id)"
+        );
+        assert_eq!(
+            sql(&Dialect::MySql).unwrap(),
+            "ALTER TABLE product RENAME COLUMN name TO title;\n\
+            ALTER TABLE product MODIFY COLUMN title text;\n\
+            ALTER TABLE product ADD CONSTRAINT product_title_key UNIQUE (title);\n\
+            DROP INDEX product_code_idx ON product;\n\
+            ALTER TABLE product MODIFY COLUMN price int NOT NULL DEFAULT 0;\n\
+            ALTER TABLE product MODIFY COLUMN is_active boolean NOT NULL;\n\
+            ALTER TABLE product DROP FOREIGN KEY product_created_by_fkey;\n\
+            ALTER TABLE product ADD CONSTRAINT product_created_by_fkey FOREIGN KEY (created_by) REFERENCES vendor(vendor_id)"
+        );
+        assert!(matches!(
+            sql(&Dialect::Sqlite),
+            Err(Error::UnsupportedOperation(_))
+        ));
+
+        let input = to_chars("product{name=title:text,product_id=*id:i32}");
+        let rename = alter_table().parse(&input).expect("must be parsed");
+        assert_eq!(
+            rename
+                .into_sql(Some(&table_lookup), &Dialect::Sqlite)
+                .unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN name TO title",
+                "ALTER TABLE product RENAME COLUMN product_id TO id"
+            ]
+        );
+        let input = to_chars("product{description=summary:text}");
+        let missing = alter_table().parse(&input).expect("must be parsed");
+        assert!(matches!(
+            missing.into_sql(Some(&table_lookup), &Dialect::Sqlite),
+            Err(Error::TableError(TableError::ColumnNotFound(_, _)))
+        ));
+    }
+
+    fn lookup_of(table_defs: &[&str]) -> TableLookup {
+        let mut table_lookup = TableLookup::new();
+        for input in table_defs {
+            let input = to_chars(input);
+            table_lookup.add_table(table_def().parse(&input).unwrap());
+        }
+        table_lookup
+    }
+
+    fn alter_sql(
+        table_lookup: &TableLookup,
+        alter: &str,
+        dialect: &Dialect,
+    ) -> Result<Vec<String>, Error> {
+        let input = to_chars(alter);
+        let alter_table = alter_table().parse(&input).expect("must be parsed");
+        alter_table.into_sql(Some(table_lookup), dialect)
+    }
+
+    #[test]
+    fn keys_are_named_in_each_dialect() {
+        let table_lookup = lookup_of(&["users{*user_id:i32}"]);
+        let input = to_chars(
+            "product{*product_id:i32,&sku:text,created_by(users):i32}",
+        );
+        let product = table_def().parse(&input).unwrap();
+        let sql = |dialect| {
+            product
+                .into_sql_statement(Some(&table_lookup), dialect)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "CREATE TABLE IF NOT EXISTS product (product_id int PRIMARY KEY NOT NULL, sku text CONSTRAINT product_sku_key UNIQUE NOT NULL, created_by int NOT NULL CONSTRAINT product_created_by_fkey REFERENCES users (user_id))"
+        );
+        assert_eq!(
+            sql(&Dialect::MySql),
+            "CREATE TABLE IF NOT EXISTS product (product_id int PRIMARY KEY NOT NULL, sku text NOT NULL, created_by int NOT NULL, CONSTRAINT product_sku_key UNIQUE (sku), CONSTRAINT product_created_by_fkey FOREIGN KEY (created_by) REFERENCES users(user_id))"
+        );
+
+        let input = to_chars("product{+&code:text?,+owner_id(users):i32?}");
+        let add_columns = alter_table().parse(&input).unwrap();
+        let sql = |dialect| {
+            add_columns
+                .into_sql_statements(Some(&table_lookup), dialect)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            vec![
+                "ALTER TABLE product ADD COLUMN code text CONSTRAINT product_code_key UNIQUE",
+                "ALTER TABLE product ADD COLUMN owner_id int CONSTRAINT product_owner_id_fkey REFERENCES users (user_id)",
+            ]
+        );
+        assert_eq!(
+            sql(&Dialect::MySql),
+            vec![
+                "ALTER TABLE product ADD COLUMN code text",
+                "ALTER TABLE product ADD CONSTRAINT product_code_key UNIQUE (code)",
+                "ALTER TABLE product ADD COLUMN owner_id int",
+                "ALTER TABLE product ADD CONSTRAINT product_owner_id_fkey FOREIGN KEY (owner_id) REFERENCES users(user_id)",
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_column_renames_its_keys() {
+        let table_lookup = lookup_of(&[
+            "users{*user_id:i32}",
+            "product{*product_id:i32,&sku:text,created_by(users):i32}",
+        ]);
+        let rename =
+            "product{sku=&stock_code:text,created_by=owner_id(users):i32}";
+        assert_eq!(
+            alter_sql(&table_lookup, rename, &Dialect::PostgreSql).unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN sku TO stock_code",
+                "ALTER TABLE product RENAME CONSTRAINT product_sku_key TO product_stock_code_key",
+                "ALTER TABLE product RENAME COLUMN created_by TO owner_id",
+                "ALTER TABLE product RENAME CONSTRAINT product_created_by_fkey TO product_owner_id_fkey",
+            ]
+        );
+        assert_eq!(
+            alter_sql(&table_lookup, rename, &Dialect::MySql).unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN sku TO stock_code",
+                "ALTER TABLE product RENAME INDEX product_sku_key TO product_stock_code_key",
+                "ALTER TABLE product DROP FOREIGN KEY product_created_by_fkey",
+                "ALTER TABLE product RENAME COLUMN created_by TO owner_id",
+                "ALTER TABLE product ADD CONSTRAINT product_owner_id_fkey FOREIGN KEY (owner_id) REFERENCES users(user_id)",
+            ]
+        );
+        assert_eq!(
+            alter_sql(&table_lookup, rename, &Dialect::Sqlite).unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN sku TO stock_code",
+                "ALTER TABLE product RENAME COLUMN created_by TO owner_id",
+            ]
+        );
+
+        // the renamed keys are found by the next alteration
+        let table_lookup = lookup_of(&[
+            "users{*user_id:i32}",
+            "product{*product_id:i32,&stock_code:text,owner_id(users):i32}",
+        ]);
+        let drop_keys =
+            "product{stock_code=stock_code:text,owner_id=owner_id:i32}";
+        assert_eq!(
+            alter_sql(&table_lookup, drop_keys, &Dialect::PostgreSql).unwrap(),
+            vec![
+                "ALTER TABLE product DROP CONSTRAINT product_stock_code_key",
+                "ALTER TABLE product DROP CONSTRAINT product_owner_id_fkey",
+            ]
+        );
+        assert_eq!(
+            alter_sql(&table_lookup, drop_keys, &Dialect::MySql).unwrap(),
+            vec![
+                "ALTER TABLE product DROP INDEX product_stock_code_key",
+                "ALTER TABLE product DROP FOREIGN KEY product_owner_id_fkey",
+            ]
+        );
+        assert!(matches!(
+            alter_sql(&table_lookup, drop_keys, &Dialect::Sqlite),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn primary_key_is_altered_in_each_dialect() {
+        let table_lookup = lookup_of(&["tag{name:text}"]);
+        let add_primary = "tag{name=*name:text}";
+        assert_eq!(
+            alter_sql(&table_lookup, add_primary, &Dialect::PostgreSql)
+                .unwrap(),
+            vec!["ALTER TABLE tag ADD CONSTRAINT tag_pkey PRIMARY KEY (name)"]
+        );
+        assert_eq!(
+            alter_sql(&table_lookup, add_primary, &Dialect::MySql).unwrap(),
+            vec!["ALTER TABLE tag ADD CONSTRAINT tag_pkey PRIMARY KEY (name)"]
+        );
+        assert!(matches!(
+            alter_sql(&table_lookup, add_primary, &Dialect::Sqlite),
+            Err(Error::UnsupportedOperation(_))
+        ));
+
+        let table_lookup = lookup_of(&["tag{*name:text}"]);
+        let drop_primary = "tag{name=name:text}";
+        assert_eq!(
+            alter_sql(&table_lookup, drop_primary, &Dialect::PostgreSql)
+                .unwrap(),
+            vec!["ALTER TABLE tag DROP CONSTRAINT tag_pkey"]
+        );
+        assert_eq!(
+            alter_sql(&table_lookup, drop_primary, &Dialect::MySql).unwrap(),
+            vec!["ALTER TABLE tag DROP PRIMARY KEY"]
+        );
+        assert!(matches!(
+            alter_sql(&table_lookup, drop_primary, &Dialect::Sqlite),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn alter_column_errors() {
+        let table_lookup = lookup_of(&["tag{tag_id:i32,name:text}"]);
+        let input = to_chars("tag{name=name:text?}");
+        let alter_tag = alter_table().parse(&input).unwrap();
+        assert!(matches!(
+            alter_tag.into_sql(None, &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::NoSuppliedTableLookup))
+        ));
+        assert!(matches!(
+            alter_sql(
+                &table_lookup,
+                "product{name=name:text?}",
+                &Dialect::PostgreSql
+            ),
+            Err(Error::TableError(TableError::TableNotFound(_)))
+        ));
+        assert!(matches!(
+            alter_sql(
+                &table_lookup,
+                "tag{tag_id=tag_id:s32}",
+                &Dialect::PostgreSql
+            ),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        assert!(matches!(
+            alter_sql(&table_lookup, "tag{name=name:text?}", &Dialect::Sqlite),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        assert!(matches!(
+            alter_tag
+                .into_sql_statements(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn indexes_are_created() {
+        let input = to_chars(
+            "product{*product_id:i32,@name:text,&@code:text,category_id:i32,@(category_id,name),&@(code,category_id)}",
+        );
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            product.to_string(),
+            "product{*product_id:i32,@name:text,&@code:text,category_id:i32,@(category_id,name),&@(code,category_id)}"
+        );
+        assert_eq!(
+            product.into_sql(None, &Dialect::PostgreSql).unwrap(),
+            vec![
+                "CREATE TABLE IF NOT EXISTS product (product_id int PRIMARY KEY NOT NULL, name text NOT NULL, code text NOT NULL, category_id int NOT NULL)",
+                "CREATE INDEX IF NOT EXISTS product_name_idx ON product (name)",
+                "CREATE UNIQUE INDEX IF NOT EXISTS product_code_idx ON product (code)",
+                "CREATE INDEX IF NOT EXISTS product_category_id_name_idx ON product (category_id, name)",
+                "CREATE UNIQUE INDEX IF NOT EXISTS product_code_category_id_idx ON product (code, category_id)",
+            ]
+        );
+        assert!(matches!(
+            product.sql_indexes(&Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        let statement = crate::ast::Statement::Create(product.clone());
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "CREATE TABLE IF NOT EXISTS product (product_id int PRIMARY KEY NOT NULL, name text NOT NULL, code text NOT NULL, category_id int NOT NULL)"
+        );
+        assert_eq!(
+            statement
+                .into_multiple_sql(None, &Dialect::PostgreSql)
+                .unwrap(),
+            product.into_sql(None, &Dialect::PostgreSql).unwrap()
+        );
+
+        let mut table_lookup = TableLookup::new();
+        table_lookup.add_table(product);
+        let input = to_chars("product{name=@title:text,code=@code:text}");
+        let alter_product =
+            alter_table().parse(&input).expect("must be parsed");
+        let sql =
+            |dialect| alter_product.into_sql(Some(&table_lookup), dialect);
+        assert_eq!(
+            sql(&Dialect::PostgreSql).unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN name TO title",
+                "ALTER INDEX product_name_idx RENAME TO product_title_idx",
+                "DROP INDEX IF EXISTS product_code_idx",
+                "CREATE INDEX IF NOT EXISTS product_code_idx ON product (code)",
+            ]
+        );
+        assert_eq!(
+            sql(&Dialect::MySql).unwrap(),
+            vec![
+                "ALTER TABLE product RENAME COLUMN name TO title",
+                "ALTER TABLE product RENAME INDEX product_name_idx TO product_title_idx",
+                "DROP INDEX product_code_idx ON product",
+                "CREATE INDEX product_code_idx ON product (code(255))",
+            ]
+        );
+        assert_eq!(
+            sql(&Dialect::Sqlite).unwrap(),
+            vec![
+                "DROP INDEX IF EXISTS product_name_idx",
+                "ALTER TABLE product RENAME COLUMN name TO title",
+                "CREATE INDEX IF NOT EXISTS product_title_idx ON product (title)",
+                "DROP INDEX IF EXISTS product_code_idx",
+                "CREATE INDEX IF NOT EXISTS product_code_idx ON product (code)",
+            ]
+        );
+
+        let input = to_chars("product{+@sku:text?}");
+        let add_sku = alter_table().parse(&input).expect("must be parsed");
+        assert_eq!(
+            add_sku.into_sql(None, &Dialect::PostgreSql).unwrap(),
+            vec![
+                "ALTER TABLE product ADD COLUMN sku text",
+                "CREATE INDEX IF NOT EXISTS product_sku_idx ON product (sku)",
+            ]
+        );
+        assert_eq!(
+            add_sku
+                .into_sql_statements(None, &Dialect::PostgreSql)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["ALTER TABLE product ADD COLUMN sku text"]
+        );
+        assert_eq!(
+            add_sku.sql_indexes(&Dialect::MySql).unwrap(),
+            vec!["CREATE INDEX product_sku_idx ON product (sku(255))"]
+        );
+    }
+
+    #[test]
+    fn text_indexes_in_mysql() {
+        let input = to_chars(
+            "product{*product_id:i32,@name:text,&@sku:i32,category_id:i32,@(category_id,name),@info:json}",
+        );
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert!(matches!(
+            product.sql_indexes(&Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        assert_eq!(product.sql_indexes(&Dialect::PostgreSql).unwrap().len(), 4);
+        let input = to_chars(
+            "product{*product_id:i32,@name:text,&@sku:i32,category_id:i32,@(category_id,name)}",
+        );
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            product.sql_indexes(&Dialect::MySql).unwrap(),
+            vec![
+                "CREATE INDEX product_name_idx ON product (name(255))",
+                "CREATE UNIQUE INDEX product_sku_idx ON product (sku)",
+                "CREATE INDEX product_category_id_name_idx ON product (category_id, name(255))",
+            ]
+        );
+    }
+
+    #[test]
+    fn indexes_in_sqlite() {
+        let input =
+            to_chars("product{*product_id:i32,&@code:text,@(code,product_id)}");
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            product.sql_indexes(&Dialect::Sqlite).unwrap(),
+            vec![
+                "CREATE UNIQUE INDEX IF NOT EXISTS product_code_idx ON product (code)",
+                "CREATE INDEX IF NOT EXISTS product_code_product_id_idx ON product (code, product_id)",
+            ]
+        );
+        let input = to_chars("product{*product_id:i32,code:text}");
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert!(!product.has_indexes());
+        assert!(product.sql_indexes(&Dialect::Sqlite).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_index_is_an_error() {
+        for input in [
+            "product{*product_id:i32,@(code}",
+            "product{*product_id:i32,@()}",
+            "product{*product_id:i32,@}",
+        ] {
+            let input = to_chars(input);
+            assert!(table_def().parse(&input).is_err());
+        }
+        let input = to_chars("product{*product_id:i32,@(product_id,name)}");
+        let product = table_def().parse(&input).expect("must be parsed");
+        assert!(matches!(
+            product.into_sql(None, &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::ColumnNotFound(_, _)))
+        ));
     }
 
     #[test]
@@ -950,7 +2346,8 @@ mod tests {
                         },
                         foreign: None,
                     },
-                ]
+                ],
+                constraints: vec![],
             }
         )
     }
@@ -1041,8 +2438,103 @@ mod tests {
                         },
                         foreign: None,
                     },
-                ]
+                ],
+                constraints: vec![],
             }.to_string()
         )
     }
+
+    #[test]
+    fn composite_primary_and_foreign_keys() {
+        let mut table_lookup = TableLookup::new();
+        let input = to_chars(
+            "order_line{*order_id:i32,*line_no:i32,product_id(product):i32}",
+        );
+        let order_line = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            order_line
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap_err()
+                .to_string(),
+            "Table join is specified, but no table lookup is supplied"
+        );
+        let input = to_chars("product{*product_id:s32,name:text}");
+        table_lookup.add_table(table_def().parse(&input).unwrap());
+        assert_eq!(
+            order_line
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "CREATE TABLE IF NOT EXISTS order_line (order_id int NOT NULL, line_no int NOT NULL, product_id int NOT NULL CONSTRAINT order_line_product_id_fkey REFERENCES product (product_id), PRIMARY KEY (order_id, line_no))"
+        );
+        table_lookup.add_table(order_line);
+
+        let input = to_chars(
+            "shipment{*shipment_id:s32,order_id:i32,line_no:i32,(order_id,line_no)(order_line),&(order_id,line_no)}",
+        );
+        let shipment = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            shipment.constraints,
+            vec![
+                TableConstraint::ForeignKey {
+                    columns: vec![
+                        ColumnName {
+                            name: "order_id".into()
+                        },
+                        ColumnName {
+                            name: "line_no".into()
+                        },
+                    ],
+                    foreign_table: TableName {
+                        name: "order_line".into()
+                    },
+                    referred_columns: vec![],
+                },
+                TableConstraint::Unique(vec![
+                    ColumnName {
+                        name: "order_id".into()
+                    },
+                    ColumnName {
+                        name: "line_no".into()
+                    },
+                ]),
+            ]
+        );
+        assert_eq!(
+            shipment.to_string(),
+            "shipment{*shipment_id:s32,order_id:i32,line_no:i32,(order_id,line_no)(order_line),&(order_id,line_no)}"
+        );
+        assert_eq!(
+            shipment
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "CREATE TABLE IF NOT EXISTS shipment (shipment_id SERIAL PRIMARY KEY NOT NULL, order_id int NOT NULL, line_no int NOT NULL, CONSTRAINT shipment_order_id_line_no_fkey FOREIGN KEY (order_id, line_no) REFERENCES order_line(order_id, line_no), CONSTRAINT shipment_order_id_line_no_key UNIQUE (order_id, line_no))"
+        );
+
+        let input = to_chars("shipment{order_id(order_line):i32}");
+        let shipment = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            shipment
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap_err()
+                .to_string(),
+            "The foreign key columns does not match the primary columns of table: `order_line`"
+        );
+
+        let input = to_chars("tag{*tag_id:s32,name:text,*(tag_id,name)}");
+        let tag = table_def().parse(&input).expect("must be parsed");
+        assert!(matches!(
+            tag.into_sql_statement(None, &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::DuplicatePrimaryKey(_)))
+        ));
+        let input = to_chars("tag{tag_id:i32,name:text,*(tag_id,name)}");
+        let tag = table_def().parse(&input).expect("must be parsed");
+        assert_eq!(
+            tag.into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "CREATE TABLE IF NOT EXISTS tag (tag_id int NOT NULL, name text NOT NULL, PRIMARY KEY (tag_id, name))"
+        );
+    }
 }
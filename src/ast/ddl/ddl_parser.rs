@@ -4,7 +4,11 @@ use super::*;
 use crate::{
     ast::{
         ddl::Foreign,
-        parser::{utils::end_or_ln, *},
+        parser::{
+            diagnostic::Clause,
+            utils::{end_or_ln, in_clause},
+            *,
+        },
         Value,
     },
     data_type::data_type,
@@ -36,24 +40,87 @@ pub(crate) fn foreign<'a>() -> Parser<'a, char, Foreign> {
 /// example:
 ///     &*product_id(product):u32
 pub(crate) fn column_def<'a>() -> Parser<'a, char, ColumnDef> {
-    ((column_attributes().opt() + column() + foreign().opt() - sym(':')
-        + data_type_def())
-    .map(|(((attributes, column), foreign), data_type)| ColumnDef {
-        column,
-        attributes,
-        data_type_def: data_type,
-        foreign,
-    }))
-    .name("column_def")
+    in_clause(
+        Clause::ColumnDef,
+        &[
+            "column attribute",
+            "column",
+            "`(`foreign table`)`",
+            "`:`",
+            "data type",
+            "`?`",
+            "`(`default value`)`",
+        ],
+        (column_attributes().opt() + column() + foreign().opt() - sym(':')
+            + data_type_def())
+        .map(|(((attributes, column), foreign), data_type)| ColumnDef {
+            column,
+            attributes,
+            data_type_def: data_type,
+            foreign,
+        }),
+    )
 }
 
+#[allow(unused)]
 pub(crate) fn column_def_list<'a>() -> Parser<'a, char, Vec<ColumnDef>> {
     list_fail(column_def(), sym(',')).name("column_def_list")
 }
 
-pub(crate) fn enclosed_column_def_list<'a>() -> Parser<'a, char, Vec<ColumnDef>>
-{
-    sym('{') * column_def_list() - sym('}')
+fn enclosed_columns<'a>() -> Parser<'a, char, Vec<ColumnName>> {
+    sym('(') * list_fail(column(), sym(',')) - sym(')')
+}
+
+/// parse a constraint over multiple columns
+/// table_constraint = "*" columns
+///     | "&" columns
+///     | "@" columns
+///     | "&@" columns
+///     | columns, "(", table, ["::", column, {",", column}], ")"
+/// columns = "(", column, {",", column}, ")"
+/// example:
+///     *(order_id,line_no)
+///     @(category_id,name)
+///     (order_id,line_no)(order_line)
+pub(crate) fn table_constraint<'a>() -> Parser<'a, char, TableConstraint> {
+    (sym('*') * enclosed_columns()).map(TableConstraint::PrimaryKey)
+        | (tag("&@") * enclosed_columns()).map(TableConstraint::UniqueIndex)
+        | (sym('&') * enclosed_columns()).map(TableConstraint::Unique)
+        | (sym('@') * enclosed_columns()).map(TableConstraint::Index)
+        | (enclosed_columns()
+            + (sym('(') * table()
+                + (tag("::") * list_fail(column(), sym(','))).opt()
+                - sym(')')))
+        .map(|(columns, (foreign_table, referred_columns))| {
+            TableConstraint::ForeignKey {
+                columns,
+                foreign_table,
+                referred_columns: referred_columns.unwrap_or_default(),
+            }
+        })
+}
+
+/// the column definitions followed by the table constraints
+fn enclosed_column_defs_and_constraints<'a>(
+) -> Parser<'a, char, (Vec<ColumnDef>, Vec<TableConstraint>)> {
+    (sym('{')
+        * list_fail(
+            table_constraint().map(Either::Right)
+                | column_def().map(Either::Left),
+            sym(','),
+        )
+        - sym('}'))
+    .map(|items| {
+        let mut columns = vec![];
+        let mut constraints = vec![];
+        for item in items {
+            match item {
+                Either::Left(column) => columns.push(column),
+                Either::Right(constraint) => constraints.push(constraint),
+            }
+        }
+        (columns, constraints)
+    })
 }
 
 /// example:
@@ -66,9 +133,17 @@ pub(crate) fn enclosed_column_def_list<'a>() -> Parser<'a, char, Vec<ColumnDef>>
 /// Note: that braces `{}` are invalid when used in the path part, but can be valid when used in
 /// query part.
 /// So it is safe to use the parenthesis `()` when used in actual rest api request.
+///
+/// composite primary and foreign keys are declared after the columns:
+///   order_line{order_id(orders):s32,line_no:s32,*(order_id,line_no)}
 pub fn table_def<'a>() -> Parser<'a, char, TableDef> {
-    (table() + enclosed_column_def_list() - end_or_ln())
-        .map(|(table, columns)| TableDef { table, columns })
+    (table() + enclosed_column_defs_and_constraints() - end_or_ln()).map(
+        |(table, (columns, constraints))| TableDef {
+            table,
+            columns,
+            constraints,
+        },
+    )
 }
 
 pub fn default_value<'a>() -> Parser<'a, char, Either<Function, Value>> {
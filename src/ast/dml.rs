@@ -5,22 +5,37 @@ mod dml_parser;
 
 use crate::{
     ast::{
+        ddl::{ColumnAttribute, TableConstraint},
         BinaryOperation, ColumnName, Expr, Operator, Select, TableDef,
-        TableLookup, TableName, Value,
+        TableError, TableLookup, TableName, Value,
     },
-    parser::{column, list_fail, table, value},
-    ColumnDef, Error,
+    parser::{column, list_fail, subquery, table, value},
+    ColumnDef, Dialect, Error,
+};
+pub use dml_parser::{
+    bulk_delete, bulk_update, delete, insert, update, upsert,
 };
-pub use dml_parser::{bulk_delete, bulk_update, delete, insert, update};
 use pom::parser::tag;
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Insert {
     pub into: TableName,
     pub columns: Vec<ColumnName>,
     pub source: Source,
+    /// the columns which value is looked up with a query, where the columns
+    /// of the inserted row are substituted with their values in the row,
+    /// ie: `user{user_id,name,person_id(GET/person{id}?person.name=name)}`.
+    /// The rows only have the values of the columns without a query
+    pub column_queries: Vec<(ColumnName, Select)>,
+    /// update the existing row instead when the values conflicts on these
+    /// columns, the primary columns of the table is used when empty,
+    /// ie: `product{product_id,name}?on_conflict=product_id`
+    pub on_conflict: Option<Vec<ColumnName>>,
+    /// the columns of the inserted rows to be returned,
+    /// ie: `product{name}?returning=product_id,name`
     pub returning: Option<Vec<ColumnName>>,
 }
 
@@ -38,6 +53,8 @@ pub enum Source {
 pub struct Delete {
     pub from: TableName,
     pub condition: Option<Expr>,
+    /// the columns of the deleted rows to be returned
+    pub returning: Option<Vec<ColumnName>>,
 }
 
 /// DELETE /product{product_id}
@@ -58,6 +75,8 @@ pub struct Update {
     pub columns: Vec<ColumnName>,
     pub values: Vec<Value>, // one value for each column
     pub condition: Option<Expr>,
+    /// the columns of the updated rows to be returned
+    pub returning: Option<Vec<ColumnName>>,
 }
 
 /// PATCH /product{*product_id,name}
@@ -74,13 +93,19 @@ impl Insert {
     pub fn into_sql_statement(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Statement, Error> {
         Ok(sql::Statement::Insert {
             table_name: Into::into(&self.into),
             columns: self.columns.iter().map(|c| Into::into(c)).collect(),
             source: Box::new(sql::Query {
                 ctes: vec![],
-                body: self.source.into_sql_setexpr(table_lookup)?,
+                body: match &self.source {
+                    Source::Values(rows) if !self.column_queries.is_empty() => {
+                        self.sql_values_with_queries(rows, dialect)?
+                    }
+                    source => source.into_sql_setexpr(table_lookup, dialect)?,
+                },
                 order_by: vec![],
                 limit: None,
                 offset: None,
@@ -88,19 +113,214 @@ impl Insert {
             }),
         })
     }
+
+    /// the values of each row, where the looked up columns are converted
+    /// into subqueries which filter is using the values of the row
+    fn sql_values_with_queries(
+        &self,
+        rows: &[Vec<Value>],
+        dialect: &Dialect,
+    ) -> Result<sql::SetExpr, Error> {
+        // the rows only have the values of the columns without a query,
+        // which are the columns that can be referred to in the queries
+        let value_columns: Vec<ColumnName> = self
+            .columns
+            .iter()
+            .filter(|column| self.column_query(column).is_none())
+            .cloned()
+            .collect();
+        let rows = rows
+            .iter()
+            .map(|row| {
+                if row.len() != value_columns.len() {
+                    return Err(Error::GenericError(format!(
+                        "each row must have {} values, one for each column without a query",
+                        value_columns.len()
+                    )));
+                }
+                let mut values = row.iter();
+                self.columns
+                    .iter()
+                    .map(|column| match self.column_query(column) {
+                        Some(select) => {
+                            let mut select = select.clone();
+                            select.filter = select
+                                .filter
+                                .map(|filter| {
+                                    filter.with_row_values(
+                                        &self.into,
+                                        &value_columns,
+                                        row,
+                                    )
+                                })
+                                .transpose()?;
+                            Ok(sql::Expr::Subquery(Box::new(
+                                select.into_sql_query(None, dialect)?,
+                            )))
+                        }
+                        None => Ok(sql::Expr::Value(
+                            values
+                                .next()
+                                .expect("the row length is checked")
+                                .into_sql_value(dialect),
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(sql::SetExpr::Values(sql::Values(rows)))
+    }
+
+    fn column_query(&self, column: &ColumnName) -> Option<&Select> {
+        self.column_queries
+            .iter()
+            .find(|(c, _)| c == column)
+            .map(|(_, select)| select)
+    }
+}
+
+impl Insert {
+    /// the `ON CONFLICT` clause of an upsert, which is appended to the sql text
+    /// of the insert since it can not be expressed in the sql ast.
+    /// Mysql has no conflict target and uses `ON DUPLICATE KEY UPDATE` instead
+    pub fn sql_on_conflict(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        let on_conflict = match &self.on_conflict {
+            Some(on_conflict) => on_conflict,
+            None => return Ok(None),
+        };
+        let conflict_columns = if on_conflict.is_empty() {
+            self.key_columns(table_lookup)?
+        } else {
+            on_conflict.clone()
+        };
+        let ident = |column: &ColumnName| -> sql::Ident { Into::into(column) };
+        let update_columns: Vec<&ColumnName> = self
+            .columns
+            .iter()
+            .filter(|column| !conflict_columns.contains(column))
+            .collect();
+
+        let clause = if dialect.supports_on_conflict() {
+            let target = conflict_columns
+                .iter()
+                .map(|column| ident(column).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if update_columns.is_empty() {
+                format!("ON CONFLICT ({}) DO NOTHING", target)
+            } else {
+                let assignments = update_columns
+                    .iter()
+                    .map(|column| format!("{0} = EXCLUDED.{0}", ident(column)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "ON CONFLICT ({}) DO UPDATE SET {}",
+                    target, assignments
+                )
+            }
+        } else {
+            // assigning the key to itself leaves the existing row as is
+            let update_columns = if update_columns.is_empty() {
+                conflict_columns.iter().take(1).collect()
+            } else {
+                update_columns
+            };
+            let assignments = update_columns
+                .iter()
+                .map(|column| format!("{0} = VALUES({0})", ident(column)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("ON DUPLICATE KEY UPDATE {}", assignments)
+        };
+        Ok(Some(clause))
+    }
+
+    /// the `RETURNING` clause of the insert
+    pub fn sql_returning(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        sql_returning(&self.returning, dialect)
+    }
+
+    /// the primary columns of the table, or the unique columns
+    /// when there is no primary key
+    fn key_columns(
+        &self,
+        table_lookup: Option<&TableLookup>,
+    ) -> Result<Vec<ColumnName>, Error> {
+        let table_def = find_table_def(table_lookup, &self.into)?;
+        let primary_columns = table_def.get_primary_columns();
+        if !primary_columns.is_empty() {
+            return Ok(primary_columns
+                .into_iter()
+                .map(|column_def| column_def.column.clone())
+                .collect());
+        }
+        let unique_constraint = table_def.constraints.iter().find_map(
+            |constraint| match constraint {
+                TableConstraint::Unique(columns)
+                | TableConstraint::UniqueIndex(columns) => {
+                    Some(columns.clone())
+                }
+                _ => None,
+            },
+        );
+        let unique_column = table_def.columns.iter().find(|column_def| {
+            column_def.has_attribute(&ColumnAttribute::Unique)
+        });
+        match (unique_constraint, unique_column) {
+            (Some(columns), _) => Ok(columns),
+            (None, Some(column_def)) => Ok(vec![column_def.column.clone()]),
+            (None, None) => {
+                Err(TableError::NoPrimaryKey(self.into.to_string()).into())
+            }
+        }
+    }
 }
 
 impl Delete {
-    pub fn into_sql_statement(&self) -> Result<sql::Statement, Error> {
+    /// the `RETURNING` clause of the delete
+    pub fn sql_returning(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        sql_returning(&self.returning, dialect)
+    }
+
+    pub fn into_sql_statement(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<sql::Statement, Error> {
         Ok(sql::Statement::Delete {
             table_name: Into::into(&self.from),
-            selection: self.condition.as_ref().map(|expr| Into::into(expr)),
+            selection: self
+                .condition
+                .as_ref()
+                .map(|expr| expr.into_sql_expr(dialect))
+                .transpose()?,
         })
     }
 }
 
 impl Update {
-    pub fn into_sql_statement(&self) -> Result<sql::Statement, Error> {
+    /// the `RETURNING` clause of the update
+    pub fn sql_returning(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<Option<String>, Error> {
+        sql_returning(&self.returning, dialect)
+    }
+
+    pub fn into_sql_statement(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<sql::Statement, Error> {
         Ok(sql::Statement::Update {
             table_name: Into::into(&self.table),
             assignments: self
@@ -109,27 +329,71 @@ impl Update {
                 .zip(self.values.iter())
                 .map(|(column, value)| sql::Assignment {
                     id: Into::into(column),
-                    value: Into::into(value),
+                    value: sql::Expr::Value(value.into_sql_value(dialect)),
                 })
                 .collect(),
-            selection: self.condition.as_ref().map(|expr| Into::into(expr)),
+            selection: self
+                .condition
+                .as_ref()
+                .map(|expr| expr.into_sql_expr(dialect))
+                .transpose()?,
         })
     }
 }
 
-/// a common code for building filter from columns old value and primary columns
+/// the table definition of this table from the table lookup
+fn find_table_def<'a>(
+    table_lookup: Option<&'a TableLookup>,
+    table: &TableName,
+) -> Result<&'a TableDef, Error> {
+    let table_def = table_lookup
+        .ok_or(TableError::NoSuppliedTableLookup)?
+        .find_table(table)
+        .ok_or_else(|| TableError::TableNotFound(table.to_string()))?;
+    Ok(table_def)
+}
+
+/// the `RETURNING` clause, which is appended to the sql text of the statement
+/// since it can not be expressed in the sql ast
+fn sql_returning(
+    returning: &Option<Vec<ColumnName>>,
+    dialect: &Dialect,
+) -> Result<Option<String>, Error> {
+    let returning = match returning {
+        Some(returning) => returning,
+        None => return Ok(None),
+    };
+    if !dialect.supports_returning() {
+        return Err(Error::UnsupportedOperation(format!(
+            "`RETURNING` in {:?}",
+            dialect
+        )));
+    }
+    let columns = returning
+        .iter()
+        .map(|column| {
+            let ident: sql::Ident = Into::into(column);
+            ident.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(Some(format!("RETURNING {}", columns)))
+}
+
+/// a common code for building filter from columns old value and primary columns,
+/// all of the columns are used when the primary columns are not in the columns
 fn build_filter_from_columns(
     columns: &[ColumnName],
     old_values: &[&Value],
     primary_columns: &[&ColumnDef],
 ) -> Option<Expr> {
-    let pk_values: Vec<&Value> = primary_columns
+    let pk_column_values: Option<Vec<(&ColumnName, &Value)>> = primary_columns
         .iter()
-        .filter_map(|pk| {
+        .map(|pk| {
             columns.iter().zip(old_values.iter()).find_map(
                 |(col, old_value)| {
                     if col == &pk.column {
-                        Some(*old_value)
+                        Some((col, *old_value))
                     } else {
                         None
                     }
@@ -138,22 +402,26 @@ fn build_filter_from_columns(
         })
         .collect();
 
-    let pk_column_values: Vec<(&ColumnDef, &Value)> = primary_columns
-        .into_iter()
-        .zip(pk_values.into_iter())
-        .map(|(column_def, value)| (*column_def, value))
-        .collect();
+    let pk_column_values = match pk_column_values {
+        Some(pk_column_values) if !pk_column_values.is_empty() => {
+            pk_column_values
+        }
+        _ => columns
+            .iter()
+            .zip(old_values.iter().copied())
+            .collect::<Vec<_>>(),
+    };
 
     if let Some((column0, value0)) = pk_column_values.first() {
         let mut filter0 = Expr::BinaryOperation(Box::new(BinaryOperation {
-            left: Expr::Column(column0.column.clone()),
+            left: Expr::Column((*column0).clone()),
             operator: Operator::Eq,
             right: Expr::Value((*value0).clone()),
         }));
         for (column, value) in pk_column_values.iter().skip(1) {
             let next_filter =
                 Expr::BinaryOperation(Box::new(BinaryOperation {
-                    left: Expr::Column(column.column.clone()),
+                    left: Expr::Column((*column).clone()),
                     operator: Operator::Eq,
                     right: Expr::Value((*value).clone()),
                 }));
@@ -171,20 +439,17 @@ fn build_filter_from_columns(
 }
 
 impl BulkUpdate {
-    /// convert bulk update into sql statements
+    /// convert bulk update into sql statements, one for each row
     pub fn into_sql_statements(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<Vec<sql::Statement>, Error> {
-        let table_def = table_lookup
-            .expect("must have a table lookup")
-            .get_table_def(&self.table.name)
-            .expect("must have a table_def");
-        let updates = self.into_updates(table_def)?;
-        Ok(updates
+        let table_def = find_table_def(table_lookup, &self.table)?;
+        self.into_updates(table_def)?
             .into_iter()
-            .map(|update| update.into_sql_statement().expect("must convert"))
-            .collect())
+            .map(|update| update.into_sql_statement(dialect))
+            .collect()
     }
 
     /// convert BulkUpdate into multiple Update AST
@@ -201,11 +466,12 @@ impl BulkUpdate {
                 let new_values: Vec<&Value> =
                     row.iter().skip(columns_len).collect();
 
-                assert_eq!(
-                    old_values.len(),
-                    new_values.len(),
-                    "must the same number of records"
-                );
+                if old_values.len() != new_values.len() {
+                    return Err(Error::GenericError(
+                        "each row must have both the old and the new values of the columns"
+                            .to_string(),
+                    ));
+                }
 
                 // column and values that are changed
                 let column_new_values: Vec<(ColumnName, Value)> = self
@@ -224,7 +490,7 @@ impl BulkUpdate {
                 let (columns, new_values): (Vec<ColumnName>, Vec<Value>) =
                     column_new_values.into_iter().unzip();
 
-                Update {
+                Ok(Update {
                     table: self.table.clone(),
                     columns,
                     values: new_values,
@@ -233,30 +499,58 @@ impl BulkUpdate {
                         &old_values,
                         &table_def.get_primary_columns(),
                     ),
-                }
+                    returning: None,
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>, Error>>()?;
 
-        Ok(updates)
+        // a row with no changed column has nothing to update
+        Ok(updates
+            .into_iter()
+            .filter(|update| !update.columns.is_empty())
+            .collect())
     }
 }
 
 impl BulkDelete {
-    /// convert bulk delete into sql statements
+    /// convert bulk delete into sql statements, a single statement is used
+    /// when the rows can be matched with the primary columns of the table,
+    /// otherwise one statement for each row
+    pub fn into_sql_statements(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::Statement>, Error> {
+        let table_def = find_table_def(table_lookup, &self.from)?;
+        if self.values.is_empty() {
+            Ok(vec![])
+        } else if self.has_primary_columns(table_def) {
+            Ok(vec![self.into_single_sql_statement(table_lookup, dialect)?])
+        } else {
+            self.into_multiple_sql_statements(table_lookup, dialect)
+        }
+    }
+
+    /// convert bulk delete into sql statements, one for each row
     pub fn into_multiple_sql_statements(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<Vec<sql::Statement>, Error> {
-        let table_def = table_lookup
-            .expect("must have a table lookup")
-            .get_table_def(&self.from.name)
-            .expect("must have a table_def");
-        //TODO: create a separate branch for building delete with no Lookup table needed
-        let deletes = self.into_multiple_deletes(table_def)?;
-        Ok(deletes
+        let table_def = find_table_def(table_lookup, &self.from)?;
+        self.into_multiple_deletes(table_def)?
             .into_iter()
-            .map(|delete| delete.into_sql_statement().expect("must convert"))
-            .collect())
+            .map(|delete| delete.into_sql_statement(dialect))
+            .collect()
+    }
+
+    /// true if the table has primary columns and they are all in the columns
+    fn has_primary_columns(&self, table_def: &TableDef) -> bool {
+        let primary_columns = table_def.get_primary_columns();
+        !primary_columns.is_empty()
+            && primary_columns
+                .iter()
+                .all(|pk| self.columns.contains(&pk.column))
     }
 
     /// convert BulkDelete into multiple Delete AST
@@ -276,6 +570,7 @@ impl BulkDelete {
                         &old_values,
                         &table_def.get_primary_columns(),
                     ),
+                    returning: None,
                 }
             })
             .collect();
@@ -283,15 +578,49 @@ impl BulkDelete {
         Ok(deletes)
     }
 
+    /// the value of the key column in a row, a missing value is an error
+    /// and so is an empty value since it is rendered as `DEFAULT`
+    /// which matches no row
+    fn key_value<'v>(
+        &self,
+        row: &'v [Value],
+        key_column: &ColumnName,
+    ) -> Result<&'v Value, Error> {
+        let value = self
+            .columns
+            .iter()
+            .zip(row.iter())
+            .find(|(column, _)| column.name == key_column.name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                Error::GenericError(format!(
+                    "missing value for the key column {}",
+                    key_column.name
+                ))
+            })?;
+        match value {
+            Value::String(v) if v.is_empty() => Err(Error::GenericError(
+                format!("empty value for the key column {}", key_column.name),
+            )),
+            _ => Ok(value),
+        }
+    }
+
     /// when there is a primary of this table, use the in filter
     pub fn into_single_sql_statement(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::Statement, Error> {
-        let table_def = table_lookup
-            .expect("must have a table lookup")
-            .get_table_def(&self.from.name)
-            .expect("must have a table_def");
+        let table_def = find_table_def(table_lookup, &self.from)?;
+        if !self.has_primary_columns(table_def) {
+            return Err(TableError::NoPrimaryKey(self.from.to_string()).into());
+        }
+        if self.values.is_empty() {
+            return Err(Error::UnsupportedOperation(
+                "bulk delete with no rows".to_string(),
+            ));
+        }
 
         let primary_columns = table_def.get_primary_columns();
         if primary_columns.len() == 1 {
@@ -299,20 +628,8 @@ impl BulkDelete {
             let pk_values: Vec<Value> = self
                 .values
                 .iter()
-                .flat_map(|row| {
-                    self.columns.iter().zip(row.iter()).filter_map(
-                        |(col, value)| {
-                            if pk_column.column.name == col.name {
-                                Some(value.clone())
-                            } else {
-                                None
-                            }
-                        },
-                    )
-                })
-                .collect();
-
-            assert!(!pk_values.is_empty());
+                .map(|row| self.key_value(row, &pk_column.column).cloned())
+                .collect::<Result<_, Error>>()?;
 
             let delete = Delete {
                 from: self.from.clone(),
@@ -325,11 +642,46 @@ impl BulkDelete {
                         right: Expr::MultiValue(pk_values),
                     },
                 ))),
+                returning: None,
             };
-            delete.into_sql_statement()
+            delete.into_sql_statement(dialect)
         } else {
-            //TODO: must return an Err where pirmary key is not found on this table
-            panic!("This is only applicable for table with primary column and only 1 primary column");
+            // the composite primary key is matched with row values,
+            // ie: `(order_id, line_no) IN ((1, 1), (1, 2))`
+            let pk_rows: Vec<sql::Expr> = self
+                .values
+                .iter()
+                .map(|row| {
+                    primary_columns
+                        .iter()
+                        .map(|pk_column| {
+                            Ok(sql::Expr::Value(
+                                self.key_value(row, &pk_column.column)?
+                                    .into_sql_value(dialect),
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                        .map(sql::Expr::ValueList)
+                })
+                .collect::<Result<_, Error>>()?;
+
+            Ok(sql::Statement::Delete {
+                table_name: Into::into(&self.from),
+                selection: Some(sql::Expr::InList {
+                    expr: Box::new(sql::Expr::ValueList(
+                        primary_columns
+                            .iter()
+                            .map(|pk_column| {
+                                sql::Expr::Identifier(Into::into(
+                                    &pk_column.column,
+                                ))
+                            })
+                            .collect(),
+                    )),
+                    list: pk_rows,
+                    negated: false,
+                }),
+            })
         }
     }
 }
@@ -338,15 +690,21 @@ impl Source {
     fn into_sql_setexpr(
         &self,
         table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
     ) -> Result<sql::SetExpr, Error> {
         let ret = match self {
             Source::Select(select) => sql::SetExpr::Select(Box::new(
-                select.into_sql_select(table_lookup)?,
+                select.into_sql_select(table_lookup, dialect)?,
             )),
             Source::Values(rows) => sql::SetExpr::Values(sql::Values(
                 rows.iter()
                     .map(|record| {
-                        record.iter().map(|v| Into::into(v)).collect()
+                        record
+                            .iter()
+                            .map(|v| {
+                                sql::Expr::Value(v.into_sql_value(dialect))
+                            })
+                            .collect()
                     })
                     .collect(),
             )),
@@ -359,11 +717,97 @@ impl Source {
     }
 }
 
+/// write the columns separated with comma
+fn fmt_columns(f: &mut fmt::Formatter, columns: &[ColumnName]) -> fmt::Result {
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        write!(f, "{}", column)?;
+    }
+    Ok(())
+}
+
+/// write the `returning=` clause, where `sep` is `?` when it is the first
+/// clause of the query or `&` when it follows the filter
+fn fmt_returning(
+    f: &mut fmt::Formatter,
+    sep: char,
+    returning: &Option<Vec<ColumnName>>,
+) -> fmt::Result {
+    if let Some(returning) = returning {
+        write!(f, "{}returning=", sep)?;
+        fmt_columns(f, returning)?;
+    }
+    Ok(())
+}
+
+/// only the header of the insert is displayed, the values are in the
+/// csv body that follows it
+impl fmt::Display for Insert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{{", self.into)?;
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", column)?;
+            if let Some(select) = self.column_query(column) {
+                write!(f, "(GET/{})", select)?;
+            }
+        }
+        write!(f, "}}")?;
+        let mut sep = '?';
+        if let Some(on_conflict) = &self.on_conflict {
+            write!(f, "?on_conflict")?;
+            if !on_conflict.is_empty() {
+                write!(f, "=")?;
+                fmt_columns(f, on_conflict)?;
+            }
+            sep = '&';
+        }
+        fmt_returning(f, sep, &self.returning)
+    }
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{{", self.table)?;
+        for (i, (column, value)) in
+            self.columns.iter().zip(self.values.iter()).enumerate()
+        {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", column, value)?;
+        }
+        write!(f, "}}")?;
+        let mut sep = '?';
+        if let Some(condition) = &self.condition {
+            write!(f, "?{}", condition)?;
+            sep = '&';
+        }
+        fmt_returning(f, sep, &self.returning)
+    }
+}
+
+impl fmt::Display for Delete {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.from)?;
+        let mut sep = '?';
+        if let Some(condition) = &self.condition {
+            write!(f, "?{}", condition)?;
+            sep = '&';
+        }
+        fmt_returning(f, sep, &self.returning)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ast::{
-        expr::BinaryOperation, parser::utils::to_chars, Operator,
+        expr::BinaryOperation, parser::utils::to_chars, Operator, Statement,
     };
 
     #[test]
@@ -375,8 +819,9 @@ mod tests {
         );
         let ret = insert().parse(&input).expect("must be parsed");
         println!("{:#?}", ret);
-        let statement: sql::Statement =
-            ret.into_sql_statement(None).expect("must not fail");
+        let statement: sql::Statement = ret
+            .into_sql_statement(None, &Dialect::PostgreSql)
+            .expect("must not fail");
         assert_eq!(
             statement.to_string(),
             "INSERT INTO product (product_id, created_by, created, is_active) VALUES "
@@ -409,11 +854,241 @@ mod tests {
                     ColumnName {
                         name: "name".into()
                     },
-                ])
+                ]),
+                column_queries: vec![],
+                on_conflict: None,
             }
         );
     }
 
+    #[test]
+    fn test_insert_with_column_query() {
+        let input = to_chars(
+            "user{user_id,name,person_id(GET/person{id}?person.name=name)}",
+        );
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        assert_eq!(
+            ret.to_string(),
+            "user{user_id,name,person_id(GET/person{id}?person.name=eq.name)}"
+        );
+        assert_eq!(ret.columns.len(), 3);
+        assert_eq!(ret.column_queries.len(), 1);
+        ret.source = Source::Values(vec![
+            vec![Value::Integer(1), Value::String("TOM JONES".into())],
+            vec![Value::Integer(2), Value::String("JANE DOE".into())],
+        ]);
+        let statement = Statement::Insert(ret);
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "INSERT INTO user (user_id, name, person_id) VALUES \
+            (1, 'TOM JONES', (SELECT id FROM person WHERE person.name = 'TOM JONES')), \
+            (2, 'JANE DOE', (SELECT id FROM person WHERE person.name = 'JANE DOE'))"
+        );
+        let (sql, values) = statement
+            .into_sql_with_params(None, &Dialect::PostgreSql)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO user (user_id, name, person_id) VALUES \
+            ($1, $2, (SELECT id FROM person WHERE person.name = $3)), \
+            ($4, $5, (SELECT id FROM person WHERE person.name = $6))"
+        );
+        assert_eq!(values.len(), 6);
+    }
+
+    #[test]
+    fn column_query_only_replaces_the_row_columns() {
+        let input = to_chars(
+            "user{user_id,person_id(GET/person{id}?person.name=name&age=gt.18&person.user_id=user.user_id),name}",
+        );
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        ret.source = Source::Values(vec![
+            vec![Value::Integer(1), Value::String("TOM JONES".into())],
+            vec![Value::Integer(2), Value::String("".into())],
+        ]);
+        let mut statement = Statement::Insert(ret.clone());
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "INSERT INTO user (user_id, person_id, name) VALUES \
+            (1, (SELECT id FROM person WHERE person.name = 'TOM JONES' AND age > 18 AND person.user_id = 1), 'TOM JONES'), \
+            (2, (SELECT id FROM person WHERE person.name IS NULL AND age > 18 AND person.user_id = 2), DEFAULT)"
+        );
+
+        // a row has one value for each column without a query
+        ret.source = Source::Values(vec![vec![Value::Integer(3)]]);
+        statement = Statement::Insert(ret.clone());
+        assert!(matches!(
+            statement.into_sql_statement(None, &Dialect::PostgreSql),
+            Err(Error::GenericError(_))
+        ));
+        ret.source = Source::Values(vec![vec![
+            Value::Integer(3),
+            Value::Null,
+            Value::String("JOHN".into()),
+        ]]);
+        statement = Statement::Insert(ret);
+        assert!(matches!(
+            statement.into_sql_statement(None, &Dialect::PostgreSql),
+            Err(Error::GenericError(_))
+        ));
+
+        // a null value can only be compared with eq and neq
+        let input = to_chars(
+            "user{user_id,name,person_id(GET/person{id}?person.name=gt.name)}",
+        );
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        ret.source = Source::Values(vec![vec![
+            Value::Integer(1),
+            Value::String("".into()),
+        ]]);
+        assert!(matches!(
+            Statement::Insert(ret)
+                .into_sql_statement(None, &Dialect::PostgreSql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn column_query_in_each_dialect() {
+        let input = to_chars(
+            "user{user_id,name,person_id(GET/person{id}?person.name=name)}",
+        );
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        ret.source = Source::Values(vec![vec![
+            Value::Integer(1),
+            Value::String("TOM JONES".into()),
+        ]]);
+        let statement = Statement::Insert(ret);
+        for dialect in [Dialect::MySql, Dialect::Sqlite] {
+            let (sql, values) =
+                statement.into_sql_with_params(None, &dialect).unwrap();
+            assert_eq!(
+                sql,
+                "INSERT INTO user (user_id, name, person_id) VALUES \
+                (?, ?, (SELECT id FROM person WHERE person.name = ?))"
+            );
+            assert_eq!(values.len(), 3);
+        }
+    }
+
+    #[test]
+    fn unclosed_column_query_is_an_error() {
+        let input =
+            to_chars("user{user_id,person_id(GET/person{id}?name=user.name}");
+        assert!(insert().parse(&input).is_err());
+    }
+
+    #[test]
+    fn test_upsert_in_each_dialect() {
+        let input = to_chars("product{product_id,name}?on_conflict=product_id");
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        ret.source = Source::Values(vec![vec![
+            Value::Integer(1),
+            Value::String("shovel".into()),
+        ]]);
+        let statement = Statement::Insert(ret);
+        assert_eq!(
+            statement.into_sql(None, &Dialect::Sqlite).unwrap(),
+            "INSERT INTO product (product_id, name) VALUES (1, 'shovel') ON CONFLICT (product_id) DO UPDATE SET name = EXCLUDED.name"
+        );
+        assert_eq!(
+            statement.into_sql(None, &Dialect::MySql).unwrap(),
+            "INSERT INTO product (product_id, name) VALUES (1, 'shovel') ON DUPLICATE KEY UPDATE name = VALUES(name)"
+        );
+        assert_eq!(
+            statement.into_sql_with_params(None, &Dialect::MySql).unwrap().0,
+            "INSERT INTO product (product_id, name) VALUES (?, ?) ON DUPLICATE KEY UPDATE name = VALUES(name)"
+        );
+    }
+
+    #[test]
+    fn test_upsert() {
+        let input = to_chars(
+            "product{product_id,name,price}?on_conflict=product_id&returning=product_id",
+        );
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        assert_eq!(
+            ret.on_conflict,
+            Some(vec![ColumnName {
+                name: "product_id".into()
+            }])
+        );
+        assert!(ret.returning.is_some());
+        ret.source = Source::Values(vec![vec![
+            Value::Integer(1),
+            Value::String("shovel".into()),
+            Value::Integer(10),
+        ]]);
+        let statement = Statement::Insert(ret);
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "INSERT INTO product (product_id, name, price) VALUES (1, 'shovel', 10)"
+        );
+        assert_eq!(
+            statement.into_sql(None, &Dialect::PostgreSql).unwrap(),
+            "INSERT INTO product (product_id, name, price) VALUES (1, 'shovel', 10) ON CONFLICT (product_id) DO UPDATE SET name = EXCLUDED.name, price = EXCLUDED.price RETURNING product_id"
+        );
+        assert_eq!(
+            statement.into_sql_with_params(None, &Dialect::Sqlite).unwrap().0,
+            "INSERT INTO product (product_id, name, price) VALUES (?, ?, ?) ON CONFLICT (product_id) DO UPDATE SET name = EXCLUDED.name, price = EXCLUDED.price RETURNING product_id"
+        );
+        assert!(matches!(
+            statement.into_sql(None, &Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_upsert_on_the_primary_columns() {
+        let input = to_chars("product_tag{product_id,tag_id}?on_conflict");
+        let mut ret = insert().parse(&input).expect("must be parsed");
+        ret.source =
+            Source::Values(vec![vec![Value::Integer(1), Value::Integer(2)]]);
+        let statement = Statement::Insert(ret);
+        assert!(matches!(
+            statement.into_sql(None, &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::NoSuppliedTableLookup))
+        ));
+
+        let mut table_lookup = TableLookup::new();
+        let input =
+            to_chars("product_tag{*product_id:i32,*tag_id:i32,note:text?}");
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+        assert_eq!(
+            statement
+                .into_sql(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap(),
+            "INSERT INTO product_tag (product_id, tag_id) VALUES (1, 2) ON CONFLICT (product_id, tag_id) DO NOTHING"
+        );
+        assert_eq!(
+            statement.into_sql(Some(&table_lookup), &Dialect::MySql).unwrap(),
+            "INSERT INTO product_tag (product_id, tag_id) VALUES (1, 2) ON DUPLICATE KEY UPDATE product_id = VALUES(product_id)"
+        );
+        assert_eq!(
+            statement
+                .into_sql(Some(&table_lookup), &Dialect::Sqlite)
+                .unwrap(),
+            "INSERT INTO product_tag (product_id, tag_id) VALUES (1, 2) ON CONFLICT (product_id, tag_id) DO NOTHING"
+        );
+
+        let mut table_lookup = TableLookup::new();
+        let input = to_chars("product_tag{product_id:i32,tag_id:i32}");
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+        assert!(matches!(
+            statement.into_sql(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::NoPrimaryKey(_)))
+        ));
+    }
+
     #[test]
     fn test_update() {
         let input = to_chars(
@@ -421,7 +1096,8 @@ mod tests {
         );
         let ret = update().parse(&input).expect("must be parsed");
         println!("{:#?}", ret);
-        let statement: sql::Statement = ret.into_sql_statement().unwrap();
+        let statement: sql::Statement =
+            ret.into_sql_statement(&Dialect::PostgreSql).unwrap();
         assert_eq!(
             statement.to_string(),
             r#"UPDATE product SET description = 'I''m the new description now', is_active = false WHERE product_id = 1"#
@@ -450,9 +1126,10 @@ mod tests {
                             name: "product_id".into()
                         },),
                         operator: Operator::Eq,
-                        right: Expr::Value(Value::Number(1.0))
+                        right: Expr::Value(Value::Integer(1))
                     }
-                )))
+                ))),
+                returning: None,
             }
         )
     }
@@ -461,7 +1138,8 @@ mod tests {
         let input = to_chars(r#"product?product_id=1"#);
         let ret = delete().parse(&input).expect("must be parsed");
         println!("{:#?}", ret);
-        let statement: sql::Statement = ret.into_sql_statement().unwrap();
+        let statement: sql::Statement =
+            ret.into_sql_statement(&Dialect::PostgreSql).unwrap();
         assert_eq!(
             statement.to_string(),
             "DELETE FROM product WHERE product_id = 1"
@@ -478,13 +1156,124 @@ mod tests {
                             name: "product_id".into()
                         },),
                         operator: Operator::Eq,
-                        right: Expr::Value(Value::Number(1.0))
+                        right: Expr::Value(Value::Integer(1))
                     }
-                )))
+                ))),
+                returning: None,
             }
         );
     }
 
+    #[test]
+    fn test_returning() {
+        let sql = |statement: &Statement, dialect| {
+            statement.into_sql(None, dialect).expect("must not fail")
+        };
+
+        let input = to_chars(
+            "product{name='shovel',price=10}?product_id=eq.1&returning=product_id,name",
+        );
+        let update = update().parse(&input).expect("must be parsed");
+        assert_eq!(
+            update.to_string(),
+            "product{name='shovel',price=10}?product_id=eq.1&returning=product_id,name"
+        );
+        let statement = Statement::Update(update);
+        assert_eq!(
+            sql(&statement, &Dialect::PostgreSql),
+            "UPDATE product SET name = 'shovel', price = 10 WHERE product_id = 1 RETURNING product_id, name"
+        );
+        assert_eq!(
+            statement.into_sql_with_params(None, &Dialect::Sqlite).unwrap().0,
+            "UPDATE product SET name = ?, price = ? WHERE product_id = ? RETURNING product_id, name"
+        );
+        assert!(matches!(
+            statement.into_sql(None, &Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+
+        let input = to_chars("product?returning=product_id");
+        let delete = delete().parse(&input).expect("must be parsed");
+        assert_eq!(delete.condition, None);
+        assert_eq!(delete.to_string(), "product?returning=product_id");
+        let statement = Statement::Delete(delete);
+        assert_eq!(
+            sql(&statement, &Dialect::Sqlite),
+            "DELETE FROM product RETURNING product_id"
+        );
+        assert_eq!(
+            sql(&statement, &Dialect::PostgreSql),
+            "DELETE FROM product RETURNING product_id"
+        );
+        assert!(matches!(
+            statement.into_sql(None, &Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+
+        let input = to_chars("product{name,price}?returning=product_id");
+        let mut insert = insert().parse(&input).expect("must be parsed");
+        assert_eq!(
+            insert.to_string(),
+            "product{name,price}?returning=product_id"
+        );
+        insert.source = Source::Values(vec![vec![
+            Value::String("shovel".into()),
+            Value::Integer(10),
+        ]]);
+        let statement = Statement::Insert(insert);
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "INSERT INTO product (name, price) VALUES ('shovel', 10)"
+        );
+        assert_eq!(
+            statement
+                .into_sql_with_params(None, &Dialect::PostgreSql)
+                .unwrap()
+                .0,
+            "INSERT INTO product (name, price) VALUES ($1, $2) RETURNING product_id"
+        );
+        assert_eq!(
+            statement.into_sql_with_params(None, &Dialect::Sqlite).unwrap().0,
+            "INSERT INTO product (name, price) VALUES (?, ?) RETURNING product_id"
+        );
+        assert!(matches!(
+            statement.into_sql_with_params(None, &Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn derived_insert_has_returning() {
+        let input = to_chars("product{*product_id:s32,name:text}");
+        let table_def = crate::table_def().parse(&input).unwrap();
+        let statement =
+            Statement::Insert(table_def.derive_insert(&Dialect::PostgreSql));
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &Dialect::PostgreSql)
+                .expect("must not fail")
+                .to_string(),
+            "INSERT INTO product (product_id, name) VALUES($1, $2)"
+        );
+        assert_eq!(
+            statement.into_sql(None, &Dialect::PostgreSql).unwrap(),
+            "INSERT INTO product (product_id, name) VALUES($1, $2) RETURNING product_id, name"
+        );
+        assert!(matches!(
+            statement.into_sql(None, &Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        let statement =
+            Statement::Insert(table_def.derive_insert(&Dialect::MySql));
+        assert_eq!(
+            statement.into_sql(None, &Dialect::MySql).unwrap(),
+            "INSERT INTO product (product_id, name) VALUES($1, $2)"
+        );
+    }
+
     #[test]
     fn test_bulk_delete() {
         let input = to_chars("product{name,is_active}");
@@ -532,4 +1321,268 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_bulk_delete_composite_primary_key() {
+        let mut table_lookup = TableLookup::new();
+        let input = to_chars("order_line{*order_id:s32,*line_no:s32,qty:s32}");
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+        let bulk_delete = BulkDelete {
+            from: TableName {
+                name: "order_line".into(),
+            },
+            columns: vec![
+                ColumnName {
+                    name: "line_no".into(),
+                },
+                ColumnName {
+                    name: "order_id".into(),
+                },
+            ],
+            values: vec![
+                vec![Value::Integer(1), Value::Integer(10)],
+                vec![Value::Integer(2), Value::Integer(10)],
+            ],
+        };
+        assert_eq!(
+            bulk_delete
+                .into_single_sql_statement(
+                    Some(&table_lookup),
+                    &Dialect::PostgreSql
+                )
+                .unwrap()
+                .to_string(),
+            "DELETE FROM order_line WHERE (order_id, line_no) IN ((10, 1), (10, 2))"
+        );
+        assert_eq!(
+            bulk_delete
+                .into_single_sql_statement(Some(&table_lookup), &Dialect::MySql)
+                .unwrap()
+                .to_string(),
+            "DELETE FROM order_line WHERE (order_id, line_no) IN ((10, 1), (10, 2))"
+        );
+        let (sql, values) = Statement::BulkDelete(bulk_delete.clone())
+            .into_sql_with_params(Some(&table_lookup), &Dialect::Sqlite)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM order_line WHERE (order_id, line_no) IN ((?, ?), (?, ?))"
+        );
+        assert_eq!(values.len(), 4);
+
+        let mut short_delete = bulk_delete.clone();
+        short_delete.values[1].pop();
+        assert!(matches!(
+            short_delete.into_single_sql_statement(
+                Some(&table_lookup),
+                &Dialect::PostgreSql
+            ),
+            Err(Error::GenericError(_))
+        ));
+
+        let mut blank_delete = bulk_delete;
+        blank_delete.values[1][1] = Value::String("".into());
+        assert!(matches!(
+            blank_delete.into_single_sql_statement(
+                Some(&table_lookup),
+                &Dialect::PostgreSql
+            ),
+            Err(Error::GenericError(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulk_statements() {
+        let input = to_chars("product{name,is_active}");
+        let mut product_delete = bulk_delete().parse(&input).unwrap();
+        product_delete.values = vec![
+            vec![Value::String("go pro".into()), Value::Bool(true)],
+            vec![Value::String("shovel".into()), Value::Bool(true)],
+        ];
+        let statement = Statement::BulkDelete(product_delete);
+        assert!(matches!(
+            statement.into_sql_statements(None, &Dialect::PostgreSql),
+            Err(Error::TableError(TableError::NoSuppliedTableLookup))
+        ));
+
+        let mut table_lookup = TableLookup::new();
+        let input =
+            to_chars("product{*product_id:i32,name:text,is_active:bool}");
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+        let input = to_chars("tag{name:text,is_active:bool}");
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+        let sql = |statement: &Statement| {
+            statement
+                .into_sql_statements(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap()
+                .iter()
+                .map(|statement| statement.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // the primary column is not in the columns, so all the columns are used
+        assert_eq!(
+            sql(&statement),
+            vec![
+                "DELETE FROM product WHERE name = 'go pro' AND is_active = true",
+                "DELETE FROM product WHERE name = 'shovel' AND is_active = true",
+            ]
+        );
+        assert!(matches!(
+            statement
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::MoreThanOneStatement)
+        ));
+
+        let input = to_chars("tag{name}");
+        let mut tag_delete = bulk_delete().parse(&input).unwrap();
+        tag_delete.values = vec![vec![Value::String("new".into())]];
+        assert!(matches!(
+            tag_delete.into_single_sql_statement(
+                Some(&table_lookup),
+                &Dialect::PostgreSql
+            ),
+            Err(Error::TableError(TableError::NoPrimaryKey(_)))
+        ));
+        assert_eq!(
+            sql(&Statement::BulkDelete(tag_delete)),
+            vec!["DELETE FROM tag WHERE name = 'new'"]
+        );
+
+        let input = to_chars("product{product_id}");
+        let empty_delete = bulk_delete().parse(&input).unwrap();
+        assert!(matches!(
+            empty_delete.into_single_sql_statement(
+                Some(&table_lookup),
+                &Dialect::PostgreSql
+            ),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        let statement = Statement::BulkDelete(empty_delete.clone());
+        assert!(sql(&statement).is_empty());
+        assert!(matches!(
+            statement.into_sql(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::NoStatement)
+        ));
+        assert!(matches!(
+            statement.into_sql_with_params(
+                Some(&table_lookup),
+                &Dialect::PostgreSql
+            ),
+            Err(Error::NoStatement)
+        ));
+
+        let mut blank_delete = empty_delete;
+        blank_delete.values = vec![
+            vec![Value::String("1".into())],
+            vec![Value::String("".into())],
+        ];
+        assert!(matches!(
+            Statement::BulkDelete(blank_delete)
+                .into_sql(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::GenericError(_))
+        ));
+
+        let input = to_chars("product{product_id,name}");
+        let mut bulk_update = bulk_update().parse(&input).unwrap();
+        bulk_update.values = vec![
+            vec![
+                Value::Integer(1),
+                Value::String("go pro".into()),
+                Value::Integer(1),
+                Value::String("go pro hero4".into()),
+            ],
+            vec![Value::Integer(2), Value::String("shovel".into())],
+        ];
+        let statement = Statement::BulkUpdate(bulk_update.clone());
+        assert!(matches!(
+            statement
+                .into_sql_statements(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::GenericError(_))
+        ));
+        bulk_update.values.pop();
+        bulk_update.values.push(vec![
+            Value::Integer(2),
+            Value::String("shovel".into()),
+            Value::Integer(2),
+            Value::String("shovel".into()),
+        ]);
+        assert_eq!(
+            sql(&Statement::BulkUpdate(bulk_update.clone())),
+            vec![
+                "UPDATE product SET name = 'go pro hero4' WHERE product_id = 1"
+            ]
+        );
+        bulk_update.values.remove(0);
+        assert!(matches!(
+            Statement::BulkUpdate(bulk_update)
+                .into_sql(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::NoStatement)
+        ));
+    }
+
+    #[test]
+    fn single_statement_agrees_with_statements() {
+        let mut table_lookup = TableLookup::new();
+        let input = to_chars("product{*product_id:s32,@name:text}");
+        let product = crate::table_def().parse(&input).unwrap();
+        table_lookup.add_table(product.clone());
+        let input = to_chars("product{+@sku:text?,-name}");
+        let add_drop = crate::ast::ddl::alter_table().parse(&input).unwrap();
+        let input = to_chars("product{name=title:text}");
+        let alter_column =
+            crate::ast::ddl::alter_table().parse(&input).unwrap();
+
+        let statements = vec![
+            Statement::Insert(product.derive_insert(&Dialect::PostgreSql)),
+            Statement::Create(product),
+            Statement::AlterTable(add_drop.clone()),
+            Statement::AlterTable(alter_column),
+            Statement::ListTables,
+        ];
+        for statement in statements.iter() {
+            let single = statement
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql);
+            let multiple = statement
+                .into_sql_statements(Some(&table_lookup), &Dialect::PostgreSql);
+            match (single, multiple) {
+                (Ok(single), Ok(multiple)) => {
+                    assert_eq!(vec![single], multiple)
+                }
+                (Err(Error::MoreThanOneStatement), Ok(multiple)) => {
+                    assert!(multiple.len() > 1)
+                }
+                (Err(Error::UnsupportedOperation(_)), Err(_)) => (),
+                (single, multiple) => {
+                    panic!("disagrees: {:?} {:?}", single, multiple)
+                }
+            }
+        }
+        assert_eq!(
+            Statement::AlterTable(add_drop)
+                .into_sql_statements(None, &Dialect::PostgreSql)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "ALTER TABLE product ADD COLUMN sku text",
+                "ALTER TABLE product DROP COLUMN IF EXISTS name CASCADE"
+            ]
+        );
+        assert!(matches!(
+            statements[3]
+                .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        assert_eq!(
+            statements[3]
+                .into_multiple_sql(Some(&table_lookup), &Dialect::PostgreSql)
+                .unwrap(),
+            vec![
+                "DROP INDEX IF EXISTS product_name_idx",
+                "ALTER TABLE product RENAME COLUMN name TO title"
+            ]
+        );
+    }
 }
@@ -1,11 +1,16 @@
 //! dml parser contains algorithm for parsing restq DML syntax into
 //! a DML AST.
 //!
-use super::{column, list_fail, tag};
+use super::{column, list_fail, subquery, tag};
 use crate::{
     ast::{
         dml::{table, value, Source},
-        BulkDelete, BulkUpdate, Delete, Insert, Update, Value,
+        parser::{
+            diagnostic::Clause,
+            utils::{clause, end_or_ln, in_clause},
+            FILTER_EXPECTED,
+        },
+        BulkDelete, BulkUpdate, Delete, Expr, Insert, Select, Update, Value,
     },
     filter_expr,
     pom::parser::*,
@@ -20,15 +25,60 @@ fn columns<'a>() -> Parser<'a, char, Vec<ColumnName>> {
     list_fail(column(), sym(','))
 }
 
+/// on_conflict=product_id, or just on_conflict to use the primary columns
+fn on_conflict<'a>() -> Parser<'a, char, Vec<ColumnName>> {
+    (tag("on_conflict") * (sym('=') * columns()).opt())
+        .map(|columns| columns.unwrap_or_default())
+}
+
+/// a column of the insert, which value can be looked up with a query
+/// using the other columns of the row,
+/// ie: `person_id(GET/person{id}?person.name=name)`
+fn insert_column<'a>() -> Parser<'a, char, (ColumnName, Option<Select>)> {
+    column() + (tag("(GET/") * call(subquery)).opt()
+}
+
 /// product{product_id,created_by,created,is_active}?returning=product_id,name
+///
+/// upsert:
+///   product{product_id,name}?on_conflict=product_id&returning=product_id
+///
+/// with a column looked up from a query:
+///   user{user_id,name,person_id(GET/person{id}?person.name=name)}
 pub fn insert<'a>() -> Parser<'a, char, Insert> {
-    (table() - sym('{') + columns() - sym('}') + (sym('?') * returning()).opt())
-        .map(|((into, columns), returning)| Insert {
+    (table() - sym('{') + list_fail(insert_column(), sym(',')) - sym('}')
+        + (sym('?')
+            * ((on_conflict().map(Some) + (sym('&') * returning()).opt())
+                | returning().map(|returning| (None, Some(returning)))))
+        .opt())
+    .map(|((into, insert_columns), query)| {
+        let (on_conflict, returning) = query.unwrap_or((None, None));
+        let mut columns = vec![];
+        let mut column_queries = vec![];
+        for (column, select) in insert_columns {
+            if let Some(select) = select {
+                column_queries.push((column.clone(), select));
+            }
+            columns.push(column);
+        }
+        Insert {
             into,
             columns,
+            column_queries,
+            on_conflict,
             returning,
             source: Source::Values(vec![]),
-        })
+        }
+    })
+}
+
+/// the insert in a PUT, which updates the existing rows
+/// on the primary columns unless the conflict columns are specified
+pub fn upsert<'a>() -> Parser<'a, char, Insert> {
+    insert().map(|mut insert| {
+        insert.on_conflict.get_or_insert_with(Vec::new);
+        insert
+    })
 }
 
 fn column_value<'a>() -> Parser<'a, char, (ColumnName, Value)> {
@@ -36,28 +86,53 @@ fn column_value<'a>() -> Parser<'a, char, (ColumnName, Value)> {
 }
 
 fn column_values<'a>() -> Parser<'a, char, Vec<(ColumnName, Value)>> {
-    list_fail(column_value(), sym(','))
+    in_clause(
+        Clause::ColumnValues,
+        &["column", "`=`", "value", "`,`", "`}`"],
+        list_fail(column_value(), sym(',')),
+    )
+}
+
+/// the filter and the returning clause of update and delete,
+/// ie: `?product_id=eq.1&returning=product_id,name`
+fn condition_returning<'a>(
+) -> Parser<'a, char, (Option<Expr>, Option<Vec<ColumnName>>)> {
+    clause(
+        Clause::Filter,
+        FILTER_EXPECTED,
+        sym('?'),
+        (returning().map(|returning| (None, Some(returning)))
+            | (filter_expr().map(Some) + (sym('&') * returning()).opt()))
+            - end_or_ln(),
+    )
+    .map(|query| query.unwrap_or((None, None)))
 }
 
 /// product{description="I'm the new description now",is_active=false}?product_id=1
 pub fn update<'a>() -> Parser<'a, char, Update> {
-    (table() - sym('{') + column_values() - sym('}')
-        + (sym('?') * filter_expr()).opt())
-    .map(|((table, column_values), condition)| {
+    (table() - sym('{') + column_values() - sym('}') + condition_returning()
+        - end_or_ln())
+    .map(|((table, column_values), (condition, returning))| {
         let (columns, values) = column_values.into_iter().unzip();
         Update {
             table,
             columns,
             values,
             condition,
+            returning,
         }
     })
 }
 
-///  product?product_id=1
+///  product?product_id=1&returning=product_id
 pub fn delete<'a>() -> Parser<'a, char, Delete> {
-    (table() + (sym('?') * filter_expr()).opt())
-        .map(|(from, condition)| Delete { from, condition })
+    (table() + condition_returning() - end_or_ln()).map(
+        |(from, (condition, returning))| Delete {
+            from,
+            condition,
+            returning,
+        },
+    )
 }
 
 /// bulk delete
@@ -1,10 +1,11 @@
-use crate::ast::{ColumnName, Function, Operator, Value};
+use crate::{
+    ast::{ColumnName, Function, Operator, Select, TableName, Value},
+    Dialect, Error,
+};
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
 use std::fmt;
 
-//TODO: Should be able to do math operations
-// such as: *, +, -, /, %
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Expr {
     Column(ColumnName),
@@ -12,9 +13,12 @@ pub enum Expr {
     Value(Value),
     MultiValue(Vec<Value>),
     BinaryOperation(Box<BinaryOperation>),
+    UnaryOperation(Box<UnaryOperation>),
     /// The expressions is explicitly
     /// grouped in a parenthesis
     Nested(Box<Expr>),
+    /// a select used as an expression, ie: `(GET/users{user_id}?active=eq.true)`
+    Subquery(Box<Select>),
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -30,34 +34,378 @@ pub struct BinaryOperation {
     pub right: Expr,
 }
 
-impl Into<sql::Expr> for &Expr {
-    fn into(self) -> sql::Expr {
-        match self {
+/// an operation with a single operand, ie: `not.(a=eq.1|b=eq.2)`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct UnaryOperation {
+    pub operator: Operator,
+    pub expr: Expr,
+}
+
+impl Expr {
+    pub fn into_sql_expr(&self, dialect: &Dialect) -> Result<sql::Expr, Error> {
+        let expr = match self {
             Expr::Column(column) => {
                 sql::Expr::Identifier(sql::Ident::new(&column.name))
             }
             Expr::Function(function) => {
-                sql::Expr::Function(Into::into(function))
+                sql::Expr::Function(function.into_sql_function(dialect)?)
+            }
+            Expr::Value(value) => {
+                sql::Expr::Value(value.into_sql_value(dialect))
             }
-            Expr::Value(value) => sql::Expr::Value(Into::into(value)),
             Expr::MultiValue(values) => sql::Expr::ValueList(
                 values
-                    .into_iter()
-                    .map(|v| sql::Expr::Value(Into::into(v)))
+                    .iter()
+                    .map(|v| sql::Expr::Value(v.into_sql_value(dialect)))
                     .collect(),
             ),
-            Expr::BinaryOperation(binop) => sql::Expr::BinaryOp {
-                left: Box::new(Into::into(&binop.left)),
-                op: Into::into(&binop.operator),
-                right: Box::new(Into::into(&binop.right)),
-            },
+            Expr::BinaryOperation(binop) => binop.into_sql_expr(dialect)?,
+            Expr::UnaryOperation(unop) => unop.into_sql_expr(dialect)?,
             Expr::Nested(expr) => {
-                sql::Expr::Nested(Box::new(Into::into(expr.as_ref())))
+                sql::Expr::Nested(Box::new(expr.into_sql_expr(dialect)?))
+            }
+            // there is no table lookup here, so joins in the subquery
+            // will return an error
+            Expr::Subquery(select) => sql::Expr::Subquery(Box::new(
+                select.into_sql_query(None, dialect)?,
+            )),
+        };
+        Ok(expr)
+    }
+
+    /// replace the columns of the row in this expression with their values
+    /// in the row, the column can be qualified with the table of the row,
+    /// ie: `name` or `user.name`. The columns of the other tables, such as
+    /// `person.name`, and the subqueries are left as is.
+    /// An empty value is null since there is no default value in a filter.
+    pub(crate) fn with_row_values(
+        &self,
+        table: &TableName,
+        columns: &[ColumnName],
+        row: &[Value],
+    ) -> Result<Expr, Error> {
+        let expr = match self {
+            Expr::Column(column) => {
+                let name = column
+                    .name
+                    .strip_prefix(&format!("{}.", table.name))
+                    .unwrap_or(&column.name);
+                match columns.iter().position(|c| c.name == name) {
+                    Some(i) => match &row[i] {
+                        Value::String(v) if v.is_empty() => {
+                            Expr::Value(Value::Null)
+                        }
+                        value => Expr::Value(value.clone()),
+                    },
+                    None => self.clone(),
+                }
+            }
+            Expr::BinaryOperation(binop) => {
+                binop.with_row_values(table, columns, row)?
+            }
+            Expr::UnaryOperation(unop) => {
+                Expr::UnaryOperation(Box::new(UnaryOperation {
+                    operator: unop.operator.clone(),
+                    expr: unop.expr.with_row_values(table, columns, row)?,
+                }))
+            }
+            Expr::Nested(expr) => Expr::Nested(Box::new(
+                expr.with_row_values(table, columns, row)?,
+            )),
+            _ => self.clone(),
+        };
+        Ok(expr)
+    }
+}
+
+impl BinaryOperation {
+    /// a null value of the row is never equal to anything, so the
+    /// comparison becomes `IS NULL` or `IS NOT NULL`, the other
+    /// comparisons with a null value are an error
+    fn with_row_values(
+        &self,
+        table: &TableName,
+        columns: &[ColumnName],
+        row: &[Value],
+    ) -> Result<Expr, Error> {
+        let left = self.left.with_row_values(table, columns, row)?;
+        let right = self.right.with_row_values(table, columns, row)?;
+        let is_null = |expr: &Expr| matches!(expr, Expr::Value(Value::Null));
+        let operand = if is_null(&right) && !is_null(&self.right) {
+            Some(left.clone())
+        } else if is_null(&left) && !is_null(&self.left) {
+            Some(right.clone())
+        } else {
+            None
+        };
+        let binop = match operand {
+            Some(operand) => {
+                let operator = match self.operator {
+                    Operator::Eq => Operator::Is,
+                    Operator::Neq => Operator::IsNot,
+                    _ => {
+                        return Err(Error::UnsupportedOperation(format!(
+                            "{} with a null value in the row",
+                            self
+                        )))
+                    }
+                };
+                BinaryOperation {
+                    left: operand,
+                    operator,
+                    right: Expr::Value(Value::Null),
+                }
+            }
+            None => BinaryOperation {
+                left,
+                operator: self.operator.clone(),
+                right,
+            },
+        };
+        Ok(Expr::BinaryOperation(Box::new(binop)))
+    }
+
+    fn into_sql_expr(&self, dialect: &Dialect) -> Result<sql::Expr, Error> {
+        let left = self.left.into_sql_expr(dialect)?;
+        let expr = match self.operator {
+            Operator::In | Operator::NotIn => {
+                let negated = self.operator == Operator::NotIn;
+                match &self.right {
+                    Expr::Subquery(select) => sql::Expr::InSubquery {
+                        expr: Box::new(left),
+                        subquery: Box::new(
+                            select.into_sql_query(None, dialect)?,
+                        ),
+                        negated,
+                    },
+                    Expr::MultiValue(values) => sql::Expr::InList {
+                        expr: Box::new(left),
+                        list: values
+                            .iter()
+                            .map(|v| {
+                                sql::Expr::Value(v.into_sql_value(dialect))
+                            })
+                            .collect(),
+                        negated,
+                    },
+                    right => sql::Expr::InList {
+                        expr: Box::new(left),
+                        list: vec![right.into_sql_expr(dialect)?],
+                        negated,
+                    },
+                }
+            }
+            Operator::Is | Operator::IsNot => match &self.right {
+                Expr::Value(Value::Null) if self.operator == Operator::Is => {
+                    sql::Expr::IsNull(Box::new(left))
+                }
+                Expr::Value(Value::Null) => {
+                    sql::Expr::IsNotNull(Box::new(left))
+                }
+                _ => return Err(self.unsupported()),
+            },
+            Operator::Ilike => {
+                ilike(left, self.right.into_sql_expr(dialect)?, dialect)
+            }
+            // starts with: ILIKE 'value%'
+            // ends with: ILIKE '%value'
+            // contains: ILIKE '%value%'
+            Operator::Starts | Operator::Ends | Operator::Contains => {
+                let value = match &self.right {
+                    Expr::Value(Value::String(v)) => v,
+                    // a bare word is parsed as a column, ie: `name=starts.Jo`
+                    Expr::Column(column) => &column.name,
+                    _ => return Err(self.unsupported()),
+                };
+                if has_like_wildcard(value) {
+                    match_literally(&self.operator, left, value, dialect)
+                } else {
+                    let pattern = match self.operator {
+                        Operator::Starts => format!("{}%", value),
+                        Operator::Ends => format!("%{}", value),
+                        _ => format!("%{}%", value),
+                    };
+                    ilike(
+                        left,
+                        sql::Expr::Value(sql::Value::SingleQuotedString(
+                            pattern,
+                        )),
+                        dialect,
+                    )
+                }
+            }
+            Operator::Between => match &self.right {
+                Expr::MultiValue(values) if values.len() == 2 => {
+                    sql::Expr::Between {
+                        expr: Box::new(left),
+                        negated: false,
+                        low: Box::new(sql::Expr::Value(
+                            values[0].into_sql_value(dialect),
+                        )),
+                        high: Box::new(sql::Expr::Value(
+                            values[1].into_sql_value(dialect),
+                        )),
+                    }
+                }
+                _ => return Err(self.unsupported()),
+            },
+            Operator::IsNull => sql::Expr::IsNull(Box::new(left)),
+            Operator::IsDistinct => {
+                is_distinct(left, self.right.into_sql_expr(dialect)?)
             }
+            Operator::IsNotDistinct => sql::Expr::UnaryOp {
+                op: sql::UnaryOperator::Not,
+                expr: Box::new(sql::Expr::Nested(Box::new(is_distinct(
+                    left,
+                    self.right.into_sql_expr(dialect)?,
+                )))),
+            },
+            _ => sql::Expr::BinaryOp {
+                left: Box::new(left),
+                op: self.operator.into_sql_binary_operator()?,
+                right: Box::new(self.right.into_sql_expr(dialect)?),
+            },
+        };
+        Ok(expr)
+    }
+
+    fn unsupported(&self) -> Error {
+        Error::UnsupportedOperation(self.to_string())
+    }
+}
+
+impl UnaryOperation {
+    fn into_sql_expr(&self, dialect: &Dialect) -> Result<sql::Expr, Error> {
+        let op = match self.operator {
+            Operator::Not => sql::UnaryOperator::Not,
+            _ => return Err(Error::UnsupportedOperation(self.to_string())),
+        };
+        Ok(sql::Expr::UnaryOp {
+            op,
+            expr: Box::new(self.expr.into_sql_expr(dialect)?),
+        })
+    }
+}
+
+/// `ILIKE` is emulated with `LOWER(left) LIKE LOWER(right)`
+/// in the database which does not support it
+fn ilike(left: sql::Expr, right: sql::Expr, dialect: &Dialect) -> sql::Expr {
+    if dialect.supports_ilike() {
+        sql::Expr::BinaryOp {
+            left: Box::new(left),
+            op: sql::BinaryOperator::Ilike,
+            right: Box::new(right),
+        }
+    } else {
+        sql::Expr::BinaryOp {
+            left: Box::new(lower(left)),
+            op: sql::BinaryOperator::Like,
+            right: Box::new(lower(right)),
         }
     }
 }
 
+/// true if the value has the `LIKE` wildcards `%` and `_`,
+/// or the escape character `\`
+fn has_like_wildcard(v: &str) -> bool {
+    v.contains(['%', '_', '\\'])
+}
+
+/// A value with wildcards can not be used as a `LIKE` pattern, since the sql
+/// ast has no `ESCAPE` clause and sqlite has no default escape character.
+/// It is compared case insensitively to the start, the end or a part of the
+/// column instead:
+///  - starts: `LOWER(substr(name, 1, 3)) = LOWER('50%')`
+///  - ends: `LOWER(substr(name, -3)) = LOWER('50%')`, `right(name, 3)`
+///    in postgresql
+///  - contains: `instr(LOWER(name), LOWER('50%')) > 0`, `strpos`
+///    in postgresql
+fn match_literally(
+    operator: &Operator,
+    left: sql::Expr,
+    value: &str,
+    dialect: &Dialect,
+) -> sql::Expr {
+    let binop = |left, op, right| sql::Expr::BinaryOp {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    };
+    let number = |n: String| sql::Expr::Value(sql::Value::Number(n));
+    let len = value.chars().count();
+    let value = lower(sql::Expr::Value(sql::Value::SingleQuotedString(
+        value.into(),
+    )));
+    match operator {
+        Operator::Starts => binop(
+            lower(function(
+                "substr",
+                vec![left, number("1".into()), number(len.to_string())],
+            )),
+            sql::BinaryOperator::Eq,
+            value,
+        ),
+        Operator::Ends => {
+            let suffix = if dialect.supports_negative_substr() {
+                function("substr", vec![left, number(format!("-{}", len))])
+            } else {
+                function("right", vec![left, number(len.to_string())])
+            };
+            binop(lower(suffix), sql::BinaryOperator::Eq, value)
+        }
+        _ => binop(
+            function(dialect.position_function(), vec![lower(left), value]),
+            sql::BinaryOperator::Gt,
+            number("0".into()),
+        ),
+    }
+}
+
+/// `IS DISTINCT FROM` is not supported in sql-ast, and not all databases
+/// support it, it is emulated with:
+/// `(left <> right OR left IS NULL OR right IS NULL)
+///     AND NOT (left IS NULL AND right IS NULL)`
+fn is_distinct(left: sql::Expr, right: sql::Expr) -> sql::Expr {
+    let binop = |left, op, right| sql::Expr::BinaryOp {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    };
+    let is_null = |expr: &sql::Expr| sql::Expr::IsNull(Box::new(expr.clone()));
+    let not_equal = binop(
+        binop(
+            binop(left.clone(), sql::BinaryOperator::NotEq, right.clone()),
+            sql::BinaryOperator::Or,
+            is_null(&left),
+        ),
+        sql::BinaryOperator::Or,
+        is_null(&right),
+    );
+    let both_null =
+        binop(is_null(&left), sql::BinaryOperator::And, is_null(&right));
+    binop(
+        sql::Expr::Nested(Box::new(not_equal)),
+        sql::BinaryOperator::And,
+        sql::Expr::UnaryOp {
+            op: sql::UnaryOperator::Not,
+            expr: Box::new(sql::Expr::Nested(Box::new(both_null))),
+        },
+    )
+}
+
+fn lower(expr: sql::Expr) -> sql::Expr {
+    function("LOWER", vec![expr])
+}
+
+fn function(name: &str, args: Vec<sql::Expr>) -> sql::Expr {
+    sql::Expr::Function(sql::Function {
+        name: sql::ObjectName(vec![sql::Ident::new(name)]),
+        args,
+        over: None,
+        distinct: false,
+    })
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -75,14 +423,18 @@ impl fmt::Display for Expr {
                 write!(f, "]")
             }
             Expr::BinaryOperation(bop) => bop.fmt(f),
+            Expr::UnaryOperation(unop) => unop.fmt(f),
             Expr::Nested(expr) => write!(f, "({})", expr),
+            Expr::Subquery(select) => write!(f, "(GET/{})", select),
         }
     }
 }
 
 impl fmt::Display for BinaryOperation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.operator.needs_separator() {
+        if self.operator.is_unary() {
+            write!(f, "{}={}", self.left, self.operator)
+        } else if self.operator.needs_separator() {
             write!(f, "{}={}.{}", self.left, self.operator, self.right)
         } else {
             write!(f, "{}{}{}", self.left, self.operator, self.right)
@@ -90,6 +442,12 @@ impl fmt::Display for BinaryOperation {
     }
 }
 
+impl fmt::Display for UnaryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.operator, self.expr)
+    }
+}
+
 impl fmt::Display for ExprRename {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.expr.fmt(f)?;
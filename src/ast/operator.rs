@@ -1,3 +1,4 @@
+use crate::Error;
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
 use std::fmt;
@@ -9,21 +10,28 @@ pub enum Operator {
     Multiply,
     Divide,
     Modulus,
-    Eq,     // = ,  eq
-    Neq,    // != , neq
-    Lt,     // <,  lt
-    Lte,    // <=, lte
-    Gt,     // >, gt
-    Gte,    // >=, gte
-    And,    // AND
-    Or,     // OR
-    Like,   // LIKE, like
-    In,     // (expr) IN, in
-    NotIn,  // (expr) NOT IN, not_in
-    Is,     // (expr) IS, is
-    IsNot,  // (expr) IS NOT, is_not
-    Ilike,  // ILIKE case insensitive like, postgresql specific
-    Starts, // Starts with, which will become ILIKE 'value%'
+    Eq,            // = ,  eq
+    Neq,           // != , neq
+    Lt,            // <,  lt
+    Lte,           // <=, lte
+    Gt,            // >, gt
+    Gte,           // >=, gte
+    And,           // AND
+    Or,            // OR
+    Like,          // LIKE, like
+    In,            // (expr) IN, in
+    NotIn,         // (expr) NOT IN, not_in
+    Is,            // (expr) IS, is
+    IsNot,         // (expr) IS NOT, is_not
+    Ilike,         // ILIKE case insensitive like, postgresql specific
+    Starts,        // Starts with, which will become ILIKE 'value%'
+    Ends,          // Ends with, which will become ILIKE '%value'
+    Contains,      // Contains, which will become ILIKE '%value%'
+    Between,       // (expr) BETWEEN low AND high, between.[low,high]
+    IsNull,        // (expr) IS NULL, is_null with no operand
+    IsDistinct,    // (expr) IS DISTINCT FROM, is_distinct
+    IsNotDistinct, // (expr) IS NOT DISTINCT FROM, is_not_distinct
+    Not,           // NOT (expr), not. or !
 }
 
 impl Operator {
@@ -49,15 +57,31 @@ impl Operator {
             | Operator::Is
             | Operator::IsNot
             | Operator::Ilike
-            | Operator::Starts => true,
+            | Operator::Starts
+            | Operator::Ends
+            | Operator::Contains
+            | Operator::Between
+            | Operator::IsNull
+            | Operator::IsDistinct
+            | Operator::IsNotDistinct
+            | Operator::Not => true,
         }
     }
+
+    /// the operator has no right operand, ie: `deleted=is_null`
+    pub(crate) fn is_unary(&self) -> bool {
+        *self == Operator::IsNull
+    }
 }
 
-/// convert restq to sql_ast operator
-impl Into<sql::BinaryOperator> for &Operator {
-    fn into(self) -> sql::BinaryOperator {
-        match self {
+impl Operator {
+    /// convert restq to sql_ast operator,
+    /// the operators which are not a binary operator in sql
+    /// are converted into their corresponding sql expression instead
+    pub(crate) fn into_sql_binary_operator(
+        &self,
+    ) -> Result<sql::BinaryOperator, Error> {
+        let op = match self {
             Operator::Eq => sql::BinaryOperator::Eq,
             Operator::Neq => sql::BinaryOperator::NotEq,
             Operator::Lt => sql::BinaryOperator::Lt,
@@ -74,8 +98,21 @@ impl Into<sql::BinaryOperator> for &Operator {
             Operator::Divide => sql::BinaryOperator::Divide,
             Operator::Modulus => sql::BinaryOperator::Modulus,
             Operator::In => sql::BinaryOperator::In,
-            _ => panic!("unsupported conversion"),
-        }
+            Operator::NotIn
+            | Operator::Is
+            | Operator::IsNot
+            | Operator::Starts
+            | Operator::Ends
+            | Operator::Contains
+            | Operator::Between
+            | Operator::IsNull
+            | Operator::IsDistinct
+            | Operator::IsNotDistinct
+            | Operator::Not => {
+                return Err(Error::UnsupportedOperation(self.to_string()))
+            }
+        };
+        Ok(op)
     }
 }
 
@@ -102,6 +139,13 @@ impl fmt::Display for Operator {
             Operator::IsNot => write!(f, "is_not"),
             Operator::Ilike => write!(f, "ilike"),
             Operator::Starts => write!(f, "starts"),
+            Operator::Ends => write!(f, "ends"),
+            Operator::Contains => write!(f, "contains"),
+            Operator::Between => write!(f, "between"),
+            Operator::IsNull => write!(f, "is_null"),
+            Operator::IsDistinct => write!(f, "is_distinct"),
+            Operator::IsNotDistinct => write!(f, "is_not_distinct"),
+            Operator::Not => write!(f, "not"),
         }
     }
 }
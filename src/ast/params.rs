@@ -0,0 +1,688 @@
+//! Extracts the literal values out of a generated sql statement
+//! replacing them with placeholders, so the values can be passed
+//! as bind values of a prepared statement instead of being inlined
+//! into the sql text.
+use crate::{
+    ast::{TableDef, TableLookup, Value},
+    data_type::DataType,
+    data_value::{self, DataValue},
+    Dialect, Error,
+};
+use sql_ast::ast as sql;
+
+/// Collects the bind values of a sql statement in the order
+/// they appear in the generated sql text.
+pub(crate) struct Params<'a> {
+    /// the table definitions of the tables being queried or modified,
+    /// used as a hint in casting the values into their column type
+    table_lookup: Option<&'a TableLookup>,
+    /// the dialect determines the syntax of the placeholders
+    dialect: &'a Dialect,
+    /// the tables that are in scope, innermost last, each with the name
+    /// used to qualify its columns. A common table expression or a subquery
+    /// in the from has no table definition
+    scopes: Vec<Vec<(String, Option<&'a TableDef>)>>,
+    values: Vec<DataValue>,
+}
+
+impl<'a> Params<'a> {
+    pub(crate) fn new(
+        table_lookup: Option<&'a TableLookup>,
+        dialect: &'a Dialect,
+    ) -> Self {
+        Params {
+            table_lookup,
+            dialect,
+            scopes: vec![],
+            values: vec![],
+        }
+    }
+
+    /// the collected bind values
+    pub(crate) fn into_values(self) -> Vec<DataValue> {
+        self.values
+    }
+
+    /// replace all the literal values of this statement with placeholders
+    pub(crate) fn parameterize_statement(
+        &mut self,
+        statement: &mut sql::Statement,
+    ) -> Result<(), Error> {
+        match statement {
+            sql::Statement::Query(query) => self.parameterize_query(query)?,
+            sql::Statement::Insert {
+                table_name,
+                columns,
+                source,
+            } => {
+                self.push_table(table_name);
+                self.parameterize_query_with_columns(source, columns)?;
+            }
+            sql::Statement::Update {
+                table_name,
+                assignments,
+                selection,
+            } => {
+                self.push_table(table_name);
+                for assignment in assignments.iter_mut() {
+                    self.parameterize_expr(
+                        &mut assignment.value,
+                        Some(&assignment.id),
+                    )?;
+                }
+                if let Some(selection) = selection {
+                    self.parameterize_expr(selection, None)?;
+                }
+            }
+            sql::Statement::Delete {
+                table_name,
+                selection,
+            } => {
+                self.push_table(table_name);
+                if let Some(selection) = selection {
+                    self.parameterize_expr(selection, None)?;
+                }
+            }
+            // DDL statements can not have bind values
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn parameterize_query(
+        &mut self,
+        query: &mut sql::Query,
+    ) -> Result<(), Error> {
+        self.parameterize_query_with_columns(query, &[])
+    }
+
+    /// the columns are used as hint when the query is a `VALUES` list
+    fn parameterize_query_with_columns(
+        &mut self,
+        query: &mut sql::Query,
+        columns: &[sql::Ident],
+    ) -> Result<(), Error> {
+        for cte in query.ctes.iter_mut() {
+            self.parameterize_query(&mut cte.query)?;
+        }
+        // the common table expressions shadows the tables of the same name
+        self.scopes.push(
+            query
+                .ctes
+                .iter()
+                .map(|cte| (cte.alias.name.value.to_string(), None))
+                .collect(),
+        );
+        // the order by is in the scope of the select it orders
+        match &mut query.body {
+            sql::SetExpr::Select(select) => {
+                self.push_select(select);
+                self.parameterize_select(select)?;
+                self.parameterize_order_by(&mut query.order_by)?;
+                self.scopes.pop();
+            }
+            body => {
+                self.parameterize_set_expr(body, columns)?;
+                self.parameterize_order_by(&mut query.order_by)?;
+            }
+        }
+        self.scopes.pop();
+        Ok(())
+    }
+
+    /// the scope of the table being inserted, updated or deleted
+    fn push_table(&mut self, table_name: &sql::ObjectName) {
+        let name = table_name.to_string();
+        let table_def = self.find_table_def(&name);
+        self.scopes.push(vec![(name, table_def)]);
+    }
+
+    /// the scope of the tables in the from of the select
+    fn push_select(&mut self, select: &sql::Select) {
+        let mut tables = vec![];
+        for table_with_joins in select.from.iter() {
+            self.scope_tables(table_with_joins, &mut tables);
+        }
+        self.scopes.push(tables);
+    }
+
+    fn scope_tables(
+        &self,
+        table_with_joins: &sql::TableWithJoins,
+        tables: &mut Vec<(String, Option<&'a TableDef>)>,
+    ) {
+        let relations = std::iter::once(&table_with_joins.relation)
+            .chain(table_with_joins.joins.iter().map(|join| &join.relation));
+        for relation in relations {
+            match relation {
+                sql::TableFactor::Table { name, alias, .. } => {
+                    let table_name = name.to_string();
+                    let table_def = self.find_table_def(&table_name);
+                    let name = match alias {
+                        Some(alias) => alias.name.value.to_string(),
+                        None => table_name,
+                    };
+                    tables.push((name, table_def));
+                }
+                sql::TableFactor::Derived { alias, .. } => {
+                    if let Some(alias) = alias {
+                        tables.push((alias.name.value.to_string(), None));
+                    }
+                }
+                sql::TableFactor::NestedJoin(table_with_joins) => {
+                    self.scope_tables(table_with_joins, tables)
+                }
+            }
+        }
+    }
+
+    /// the table definition of the table name, unless the name
+    /// is a common table expression or a subquery in scope
+    fn find_table_def(&self, name: &str) -> Option<&'a TableDef> {
+        let shadowed =
+            self.scopes.iter().flatten().any(|(scope_name, table_def)| {
+                table_def.is_none() && scope_name == name
+            });
+        if shadowed {
+            None
+        } else {
+            self.table_lookup?.get_table_def(name)
+        }
+    }
+
+    fn parameterize_order_by(
+        &mut self,
+        order_by: &mut [sql::OrderByExpr],
+    ) -> Result<(), Error> {
+        for order_by_expr in order_by.iter_mut() {
+            self.parameterize_grouping(&mut order_by_expr.expr)?;
+        }
+        Ok(())
+    }
+
+    /// a number in the order by and group by is the position of the column
+    /// in the projection, it is kept inline since it is not a value
+    fn parameterize_grouping(
+        &mut self,
+        expr: &mut sql::Expr,
+    ) -> Result<(), Error> {
+        match expr {
+            sql::Expr::Value(sql::Value::Number(_)) => Ok(()),
+            _ => self.parameterize_expr(expr, None),
+        }
+    }
+
+    fn parameterize_set_expr(
+        &mut self,
+        set_expr: &mut sql::SetExpr,
+        columns: &[sql::Ident],
+    ) -> Result<(), Error> {
+        match set_expr {
+            sql::SetExpr::Select(select) => {
+                self.push_select(select);
+                self.parameterize_select(select)?;
+                self.scopes.pop();
+            }
+            sql::SetExpr::Query(query) => self.parameterize_query(query)?,
+            sql::SetExpr::SetOperation { left, right, .. } => {
+                self.parameterize_set_expr(left, columns)?;
+                self.parameterize_set_expr(right, columns)?;
+            }
+            sql::SetExpr::Values(values) => {
+                for row in values.0.iter_mut() {
+                    for (i, expr) in row.iter_mut().enumerate() {
+                        self.parameterize_expr(expr, columns.get(i))?;
+                    }
+                }
+            }
+            sql::SetExpr::ParameterizedValue(_) => (),
+        }
+        Ok(())
+    }
+
+    fn parameterize_select(
+        &mut self,
+        select: &mut sql::Select,
+    ) -> Result<(), Error> {
+        for item in select.projection.iter_mut() {
+            match item {
+                sql::SelectItem::UnnamedExpr(expr)
+                | sql::SelectItem::ExprWithAlias { expr, .. } => {
+                    self.parameterize_expr(expr, None)?
+                }
+                sql::SelectItem::QualifiedWildcard(_)
+                | sql::SelectItem::Wildcard => (),
+            }
+        }
+        for table_with_joins in select.from.iter_mut() {
+            self.parameterize_table_with_joins(table_with_joins)?;
+        }
+        if let Some(selection) = &mut select.selection {
+            self.parameterize_expr(selection, None)?;
+        }
+        for expr in select.group_by.iter_mut() {
+            self.parameterize_grouping(expr)?;
+        }
+        if let Some(having) = &mut select.having {
+            self.parameterize_expr(having, None)?;
+        }
+        Ok(())
+    }
+
+    fn parameterize_table_with_joins(
+        &mut self,
+        table_with_joins: &mut sql::TableWithJoins,
+    ) -> Result<(), Error> {
+        self.parameterize_table_factor(&mut table_with_joins.relation)?;
+        for join in table_with_joins.joins.iter_mut() {
+            self.parameterize_table_factor(&mut join.relation)?;
+            match &mut join.join_operator {
+                sql::JoinOperator::Inner(constraint)
+                | sql::JoinOperator::LeftOuter(constraint)
+                | sql::JoinOperator::RightOuter(constraint)
+                | sql::JoinOperator::FullOuter(constraint) => {
+                    if let sql::JoinConstraint::On(expr) = constraint {
+                        self.parameterize_expr(expr, None)?;
+                    }
+                }
+                sql::JoinOperator::CrossJoin
+                | sql::JoinOperator::CrossApply
+                | sql::JoinOperator::OuterApply => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn parameterize_table_factor(
+        &mut self,
+        table_factor: &mut sql::TableFactor,
+    ) -> Result<(), Error> {
+        match table_factor {
+            sql::TableFactor::Table { .. } => Ok(()),
+            sql::TableFactor::Derived { subquery, .. } => {
+                self.parameterize_query(subquery)
+            }
+            sql::TableFactor::NestedJoin(table_with_joins) => {
+                self.parameterize_table_with_joins(table_with_joins)
+            }
+        }
+    }
+
+    /// the column is used as a hint for casting the value
+    /// into the data type of the column
+    fn parameterize_expr(
+        &mut self,
+        expr: &mut sql::Expr,
+        column: Option<&sql::Ident>,
+    ) -> Result<(), Error> {
+        match expr {
+            sql::Expr::Value(value) => {
+                if let Some(placeholder) = self.bind(value, column)? {
+                    *expr = placeholder;
+                }
+            }
+            sql::Expr::IsNull(expr) | sql::Expr::IsNotNull(expr) => {
+                self.parameterize_expr(expr, None)?
+            }
+            sql::Expr::InList { expr, list, .. } => {
+                self.parameterize_expr(expr, None)?;
+                let column = column_of(expr);
+                for item in list.iter_mut() {
+                    self.parameterize_expr(item, column.as_ref())?;
+                }
+            }
+            sql::Expr::ValueList(list) => {
+                for item in list.iter_mut() {
+                    self.parameterize_expr(item, column)?;
+                }
+            }
+            sql::Expr::InSubquery { expr, subquery, .. } => {
+                self.parameterize_expr(expr, None)?;
+                self.parameterize_query(subquery)?;
+            }
+            sql::Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.parameterize_expr(expr, None)?;
+                let column = column_of(expr);
+                self.parameterize_expr(low, column.as_ref())?;
+                self.parameterize_expr(high, column.as_ref())?;
+            }
+            sql::Expr::BinaryOp { left, right, .. } => {
+                let left_column = column_of(left);
+                let right_column = column_of(right);
+                self.parameterize_expr(left, right_column.as_ref())?;
+                self.parameterize_expr(right, left_column.as_ref())?;
+            }
+            sql::Expr::UnaryOp { expr, .. }
+            | sql::Expr::Cast { expr, .. }
+            | sql::Expr::Extract { expr, .. }
+            | sql::Expr::Collate { expr, .. } => {
+                self.parameterize_expr(expr, None)?
+            }
+            sql::Expr::Nested(expr) => self.parameterize_expr(expr, column)?,
+            sql::Expr::Function(function) => {
+                for arg in function.args.iter_mut() {
+                    self.parameterize_expr(arg, None)?;
+                }
+                if let Some(over) = &mut function.over {
+                    for expr in over.partition_by.iter_mut() {
+                        self.parameterize_grouping(expr)?;
+                    }
+                    self.parameterize_order_by(&mut over.order_by)?;
+                }
+            }
+            sql::Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                if let Some(operand) = operand {
+                    self.parameterize_expr(operand, None)?;
+                }
+                for condition in conditions.iter_mut() {
+                    self.parameterize_expr(condition, None)?;
+                }
+                for result in results.iter_mut() {
+                    self.parameterize_expr(result, None)?;
+                }
+                if let Some(else_result) = else_result {
+                    self.parameterize_expr(else_result, None)?;
+                }
+            }
+            sql::Expr::Exists(query) | sql::Expr::Subquery(query) => {
+                self.parameterize_query(query)?
+            }
+            sql::Expr::Identifier(_)
+            | sql::Expr::Wildcard
+            | sql::Expr::QualifiedWildcard(_)
+            | sql::Expr::CompoundIdentifier(_) => (),
+        }
+        Ok(())
+    }
+
+    /// add the value to the bind values and return the placeholder for it,
+    /// `DEFAULT` is not a value and is left as is.
+    fn bind(
+        &mut self,
+        value: &sql::Value,
+        column: Option<&sql::Ident>,
+    ) -> Result<Option<sql::Expr>, Error> {
+        let value = match value {
+            sql::Value::Null => Value::Null,
+            sql::Value::Boolean(v) => Value::Bool(*v),
+            sql::Value::Number(v) => {
+                Value::from_number_literal(v).map_err(|e| {
+                    Error::GenericError(format!("invalid number {}: {}", v, e))
+                })?
+            }
+            sql::Value::SingleQuotedString(v)
+            | sql::Value::NationalStringLiteral(v)
+            | sql::Value::HexStringLiteral(v)
+            | sql::Value::Date(v)
+            | sql::Value::Time(v)
+            | sql::Value::Timestamp(v) => Value::String(v.to_string()),
+            sql::Value::Interval { .. } | sql::Value::Default => {
+                return Ok(None)
+            }
+        };
+        let data_value =
+            match column.and_then(|column| self.column_data_type(column)) {
+                Some(data_type) => {
+                    data_value::try_cast_data_value(&value, &data_type)?
+                }
+                None => DataValue::from(&value),
+            };
+        self.values.push(data_value);
+        Ok(Some(sql::Expr::Identifier(sql::Ident::new(
+            self.dialect.placeholder(self.values.len()),
+        ))))
+    }
+
+    /// the data type of the column in the table definition, a qualified
+    /// column is looked up in the table with that name or alias and an
+    /// unqualified column in the only table that has it, starting from the
+    /// innermost scope
+    fn column_data_type(&self, column: &sql::Ident) -> Option<DataType> {
+        let (qualifier, name) = match column.value.rsplit_once('.') {
+            Some((qualifier, name)) => (Some(qualifier), name),
+            None => (None, column.value.as_str()),
+        };
+        let column_def = |table_def: &'a TableDef| {
+            table_def
+                .columns
+                .iter()
+                .find(|column_def| column_def.column.name == name)
+        };
+        for scope in self.scopes.iter().rev() {
+            match qualifier {
+                Some(qualifier) => {
+                    if let Some((_, table_def)) =
+                        scope.iter().find(|(table, _)| table == qualifier)
+                    {
+                        return column_def((*table_def)?)
+                            .map(|column_def| column_def.data_type());
+                    }
+                }
+                None => {
+                    let mut column_defs = scope
+                        .iter()
+                        .filter_map(|(_, table_def)| column_def((*table_def)?));
+                    match (column_defs.next(), column_defs.next()) {
+                        (Some(column_def), None) => {
+                            return Some(column_def.data_type())
+                        }
+                        (Some(_), Some(_)) => return None,
+                        (None, _) => (),
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// extract the column identifier in this expression if it is a column
+fn column_of(expr: &sql::Expr) -> Option<sql::Ident> {
+    match expr {
+        sql::Expr::Identifier(ident) => Some(ident.clone()),
+        sql::Expr::CompoundIdentifier(idents) => Some(sql::Ident::new(
+            idents
+                .iter()
+                .map(|ident| ident.value.as_str())
+                .collect::<Vec<_>>()
+                .join("."),
+        )),
+        sql::Expr::Nested(expr) => column_of(expr),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{
+            ddl::table_def,
+            dml::{bulk_delete, insert, update, Source},
+            Statement, TableLookup,
+        },
+        parser::{select, set_operation},
+        to_chars, DataValue, Dialect, Value,
+    };
+
+    fn product_lookup() -> TableLookup {
+        let input = to_chars(
+            "product{*product_id:s32,name:text,description:text?,price:f64,is_active:bool}",
+        );
+        let product = table_def().parse(&input).expect("must be parsed");
+        let mut table_lookup = TableLookup::new();
+        table_lookup.add_table(product);
+        table_lookup
+    }
+
+    #[test]
+    fn select_with_params() {
+        let input = to_chars("product{name}?price=gt.100&name=eq.'go pro'");
+        let select = select().parse(&input).expect("must be parsed");
+        let statement: Statement = select.into();
+        let (sql, params) = statement
+            .into_sql_with_params(None, &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "SELECT name FROM product WHERE price > $1 AND name = $2"
+        );
+        assert_eq!(
+            params,
+            vec![DataValue::I64(100), DataValue::Text("go pro".into())]
+        );
+    }
+
+    #[test]
+    fn insert_with_params_cast_to_column_type() {
+        let table_lookup = product_lookup();
+        let input = to_chars("product{product_id,name,description,is_active}");
+        let mut insert = insert().parse(&input).expect("must be parsed");
+        insert.source = Source::Values(vec![vec![
+            Value::String("1".into()),
+            Value::String("go pro".into()),
+            Value::String("".into()),
+            Value::String("true".into()),
+        ]]);
+        let (sql, params) = Statement::Insert(insert)
+            .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "INSERT INTO product (product_id, name, description, is_active) VALUES ($1, $2, DEFAULT, $3)"
+        );
+        assert_eq!(
+            params,
+            vec![
+                DataValue::S32(1),
+                DataValue::Text("go pro".into()),
+                DataValue::Bool(true)
+            ]
+        );
+    }
+
+    #[test]
+    fn update_with_params() {
+        let table_lookup = product_lookup();
+        let input = to_chars(
+            r#"product{description="I'm new",price=9.5}?product_id=1"#,
+        );
+        let update = update().parse(&input).expect("must be parsed");
+        let (sql, params) = Statement::Update(update)
+            .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "UPDATE product SET description = $1, price = $2 WHERE product_id = $3"
+        );
+        assert_eq!(
+            params,
+            vec![
+                DataValue::Text("I'm new".into()),
+                DataValue::F64(9.5),
+                DataValue::S32(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn bulk_delete_with_params() {
+        let table_lookup = product_lookup();
+        let input = to_chars("product{product_id}");
+        let mut bulk_delete =
+            bulk_delete().parse(&input).expect("must be parsed");
+        bulk_delete.values = vec![
+            vec![Value::String("1".into())],
+            vec![Value::String("2".into())],
+        ];
+        let (sql, params) = Statement::BulkDelete(bulk_delete)
+            .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(sql, "DELETE FROM product WHERE product_id IN ($1, $2)");
+        assert_eq!(params, vec![DataValue::S32(1), DataValue::S32(2)]);
+    }
+
+    #[test]
+    fn ordinals_are_kept_inline() {
+        let input = to_chars(
+            "product{name,count(product_id)}?price=gt.10&group_by=1&order_by=2.desc",
+        );
+        let statement: Statement =
+            select().parse(&input).expect("must be parsed").into();
+        let (sql, params) = statement
+            .into_sql_with_params(None, &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "SELECT name, count(product_id) FROM product WHERE price > $1 GROUP BY 1 ORDER BY 2 DESC"
+        );
+        assert_eq!(params, vec![DataValue::I64(10)]);
+    }
+
+    #[test]
+    fn invalid_value_of_column_is_an_error() {
+        let table_lookup = product_lookup();
+        let input = to_chars("product{price='cheap'}?product_id=1");
+        let update = update().parse(&input).expect("must be parsed");
+        assert!(Statement::Update(update)
+            .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+            .is_err());
+    }
+
+    #[test]
+    fn qualified_columns_are_cast_to_their_table_column_type() {
+        let mut table_lookup = product_lookup();
+        let input = to_chars("users{*user_id:s32,name:text,price:f32}");
+        table_lookup.add_table(table_def().parse(&input).unwrap());
+        let sql_with_params = |input: &str| {
+            let input = to_chars(input);
+            let statement: Statement =
+                select().parse(&input).expect("must be parsed").into();
+            statement
+                .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+                .expect("must not fail")
+        };
+
+        let (sql, params) = sql_with_params(
+            "product<-users:product_id=user_id?users.price=gt.10&product.price=gt.100",
+        );
+        assert_eq!(
+            sql,
+            "SELECT * FROM product LEFT JOIN users ON product.product_id = users.user_id WHERE users.price > $1 AND product.price > $2"
+        );
+        assert_eq!(params, vec![DataValue::F32(10.0), DataValue::F64(100.0)]);
+
+        let (_, params) = sql_with_params(
+            "product=>p<-users=>u:product_id=user_id?u.price=gt.10&p.price=gt.100",
+        );
+        assert_eq!(params, vec![DataValue::F32(10.0), DataValue::F64(100.0)]);
+
+        // the column is in both tables
+        let (_, params) =
+            sql_with_params("product<-users:product_id=user_id?price=gt.10");
+        assert_eq!(params, vec![DataValue::I64(10)]);
+
+        // the common table expression shadows the product table
+        let (_, params) = sql_with_params(
+            "product?product.price=gt.1&with=product:(GET/users{price}?price=gt.10)",
+        );
+        assert_eq!(params, vec![DataValue::F32(10.0), DataValue::I64(1)]);
+
+        let input = to_chars(
+            "(GET/product{name}?price=gt.1)union(GET/users{name}?price=gt.1)",
+        );
+        let statement = Statement::SetOperation(
+            set_operation().parse(&input).expect("must be parsed"),
+        );
+        let (_, params) = statement
+            .into_sql_with_params(Some(&table_lookup), &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(params, vec![DataValue::F64(1.0), DataValue::F32(1.0)]);
+    }
+}
@@ -1,9 +1,11 @@
 use crate::ast::*;
-use pom::parser::{call, is_a, one_of, sym, tag, Parser};
+use diagnostic::Clause;
+use pom::parser::{call, empty, is_a, list, none_of, one_of, sym, tag, Parser};
 use std::iter::FromIterator;
 pub use utils::list_fail;
 use utils::*;
 
+pub mod diagnostic;
 pub mod utils;
 
 /// a valid identifier
@@ -37,16 +39,24 @@ fn restricted_ident<'a>() -> Parser<'a, char, &'a str> {
 }
 
 pub(crate) fn strict_ident<'a>() -> Parser<'a, char, String> {
-    !(restricted_ident() - (end_or_ln() | one_of(",&=").map(|_| ()))) * ident()
+    !(restricted_ident() - (end_or_ln() | one_of(",&=).").map(|_| ())))
+        * ident()
+}
+
+/// the part of a qualified column name, which can not be the nulls ordering
+/// at the end of an order, ie: `price.nullslast`
+fn qualified_ident<'a>() -> Parser<'a, char, String> {
+    !((tag("nullsfirst") | tag("nullslast"))
+        - (end_or_ln() | one_of(",&)").map(|_| ())))
+        * strict_ident()
 }
 
 /// column name can not be followed with direction: asc, desc
 fn column_name<'a>() -> Parser<'a, char, String> {
-    (strict_ident() - sym('.') + strict_ident() - sym('.') + strict_ident())
-        .map(|((schema, table), column)| {
-            format!("{}.{}.{}", schema, table, column)
-        })
-        | (strict_ident() - sym('.') + strict_ident())
+    (strict_ident() - sym('.') + qualified_ident() - sym('.')
+        + qualified_ident())
+    .map(|((schema, table), column)| format!("{}.{}.{}", schema, table, column))
+        | (strict_ident() - sym('.') + qualified_ident())
             .map(|(table, column)| format!("{}.{}", table, column))
         | strict_ident()
         | quoted_string()
@@ -71,7 +81,7 @@ fn null<'a>() -> Parser<'a, char, Value> {
 pub(crate) fn value<'a>() -> Parser<'a, char, Value> {
     null()
         | bool().map(|v| Value::Bool(v))
-        | number().map(|n| Value::Number(n))
+        | number()
         | quoted_string().map(|v| Value::String(v))
         | single_quoted_string().map(|v| Value::String(v))
         | back_quoted_string().map(|v| Value::String(v))
@@ -83,17 +93,34 @@ pub(crate) fn multi_values<'a>() -> Parser<'a, char, Vec<Value>> {
 }
 
 fn connector<'a>() -> Parser<'a, char, Operator> {
-    tag("|").map(|_| Operator::Or) | tag("&").map(|_| Operator::And)
+    tag("|").map(|_| Operator::Or)
+        | (tag("&") - !trailing_clause()).map(|_| Operator::And)
 }
 
-fn math_operator<'a>() -> Parser<'a, char, Operator> {
-    tag("+").map(|_| Operator::Plus)
-        | tag("-").map(|_| Operator::Minus)
-        | tag("*").map(|_| Operator::Multiply)
+/// the clauses which can follow a filter and can also be parsed as a filter,
+/// ie: `&with=top_product:(GET/..)` and `&returning=product_id,name`
+fn trailing_clause<'a>() -> Parser<'a, char, ()> {
+    (tag("with=") * ident() * tag(":(GET/")).discard()
+        | (tag("returning=")
+            * list_fail(column(), sym(','))
+            * (end_or_ln() | sym(')').discard()))
+        .discard()
+}
+
+fn additive_operator<'a>() -> Parser<'a, char, Operator> {
+    tag("+").map(|_| Operator::Plus) | tag("-").map(|_| Operator::Minus)
+}
+
+fn multiplicative_operator<'a>() -> Parser<'a, char, Operator> {
+    tag("*").map(|_| Operator::Multiply)
         | tag("/").map(|_| Operator::Divide)
         | tag("%").map(|_| Operator::Modulus)
 }
 
+fn math_operator<'a>() -> Parser<'a, char, Operator> {
+    additive_operator() | multiplicative_operator()
+}
+
 fn operator<'a>() -> Parser<'a, char, Operator> {
     tag("eq").map(|_| Operator::Eq)
         | tag("neq").map(|_| Operator::Neq)
@@ -103,20 +130,31 @@ fn operator<'a>() -> Parser<'a, char, Operator> {
         | tag("gt").map(|_| Operator::Gt)
         | tag("in").map(|_| Operator::In)
         | tag("not_in").map(|_| Operator::NotIn)
+        | tag("is_not_distinct").map(|_| Operator::IsNotDistinct)
         | tag("is_not").map(|_| Operator::IsNot)
+        | tag("is_distinct").map(|_| Operator::IsDistinct)
+        | tag("is_null").map(|_| Operator::IsNull)
+        | tag("is").map(|_| Operator::Is)
         | tag("like").map(|_| Operator::Like)
         | tag("ilike").map(|_| Operator::Ilike)
         | tag("starts").map(|_| Operator::Starts)
+        | tag("ends").map(|_| Operator::Ends)
+        | tag("contains").map(|_| Operator::Contains)
+        | tag("between").map(|_| Operator::Between)
         | connector()
         | math_operator()
 }
 
-fn expr<'a>() -> Parser<'a, char, Expr> {
-    (sym('(') * call(expr) - sym(')')).map(|expr| Expr::Nested(Box::new(expr)))
+/// a single operand of an expression
+fn operand<'a>() -> Parser<'a, char, Expr> {
+    (tag("(GET/") * call(subquery))
+        .map(|select| Expr::Subquery(Box::new(select)))
+        | (sym('(') * call(expr) - sym(')'))
+            .map(|expr| Expr::Nested(Box::new(expr)))
         | multi_values().map(Expr::MultiValue)
         | null().map(Expr::Value)
         | bool().map(|v| Expr::Value(Value::Bool(v)))
-        | number().map(|v| Expr::Value(Value::Number(v)))
+        | number().map(Expr::Value)
         | function().map(Expr::Function)
         | column().map(Expr::Column)
         | value().map(Expr::Value)
@@ -133,8 +171,10 @@ fn expr_rename<'a>() -> Parser<'a, char, ExprRename> {
         .map(|(expr, rename)| ExprRename { rename, expr })
 }
 
-fn expr_projection<'a>() -> Parser<'a, char, Vec<ExprRename>> {
-    sym('{') * exprs_with_renames() - sym('}')
+/// {distinct:name,age}
+fn expr_projection<'a>() -> Parser<'a, char, (bool, Vec<ExprRename>)> {
+    (sym('{') * tag("distinct:").opt() + exprs_with_renames() - sym('}'))
+        .map(|(distinct, projection)| (distinct.is_some(), projection))
 }
 
 fn exprs<'a>() -> Parser<'a, char, Vec<Expr>> {
@@ -142,26 +182,72 @@ fn exprs<'a>() -> Parser<'a, char, Vec<Expr>> {
 }
 
 pub(crate) fn function<'a>() -> Parser<'a, char, Function> {
-    (strict_ident() - sym('(') - sym(')')).map(|name| Function {
-        name,
-        params: vec![],
-    }) | (strict_ident() - sym('(') + call(exprs) - sym(')'))
-        .map(|(name, params)| Function { name, params })
+    let name_and_args = (strict_ident() - sym('(') - sym(')'))
+        .map(|name| (name, None, vec![]))
+        | (strict_ident() - sym('(') + tag("distinct.").opt() + call(exprs)
+            - sym(')'))
+        .map(|((name, distinct), params)| (name, distinct, params));
+    (name_and_args + (tag(".over(") * call(window) - sym(')')).opt()).map(
+        |((name, distinct, params), over)| Function {
+            name,
+            params,
+            distinct: distinct.is_some(),
+            over,
+        },
+    )
 }
 
-#[allow(dead_code)]
-fn math_operation<'a>() -> Parser<'a, char, BinaryOperation> {
-    (expr() + math_operator() + expr()).map(|((left, operator), right)| {
-        BinaryOperation {
+fn partition_by<'a>() -> Parser<'a, char, Vec<Expr>> {
+    tag("partition_by=") * list(call(expr), sym(','))
+}
+
+fn window_order_by<'a>() -> Parser<'a, char, Vec<Order>> {
+    tag("order_by=") * list(call(order), sym(','))
+}
+
+/// partition_by=dept,order_by=salary.desc
+fn window<'a>() -> Parser<'a, char, Window> {
+    (partition_by() + (sym(',') * window_order_by()).opt()).map(
+        |(partition_by, order_by)| Window {
+            partition_by,
+            order_by: order_by.unwrap_or_default(),
+        },
+    ) | window_order_by().map(|order_by| Window {
+        partition_by: vec![],
+        order_by,
+    }) | empty().map(|_| Window::default())
+}
+
+/// fold the operations from left to right, ie: `a-b-c` is `(a-b)-c`
+fn fold_operations(first: Expr, rest: Vec<(Operator, Expr)>) -> Expr {
+    rest.into_iter().fold(first, |left, (operator, right)| {
+        Expr::BinaryOperation(Box::new(BinaryOperation {
             left,
             operator,
             right,
-        }
+        }))
     })
 }
 
+/// operands joined with `*`, `/` or `%`
+fn term<'a>() -> Parser<'a, char, Expr> {
+    (operand() + (multiplicative_operator() + operand()).repeat(0..))
+        .map(|(first, rest)| fold_operations(first, rest))
+}
+
+/// terms joined with `+` or `-`, `*`, `/` and `%` takes precedence
+/// ie: `price*quantity+tax`
+fn math_operation<'a>() -> Parser<'a, char, Expr> {
+    (term() + (additive_operator() + term()).repeat(0..))
+        .map(|(first, rest)| fold_operations(first, rest))
+}
+
+fn expr<'a>() -> Parser<'a, char, Expr> {
+    math_operation()
+}
+
 fn simple_operation<'a>() -> Parser<'a, char, BinaryOperation> {
-    (expr() + operator() + expr()).map(|((left, operator), right)| {
+    (operand() + operator() + operand()).map(|((left, operator), right)| {
         BinaryOperation {
             left,
             operator,
@@ -177,18 +263,48 @@ fn simple_operation_expr<'a>() -> Parser<'a, char, Expr> {
             .map(|binop| Expr::BinaryOperation(Box::new(binop)))
 }
 
+/// negation of the expression, ie: `not.(a=eq.1|b=eq.2)` or `!(a=eq.1|b=eq.2)`
+fn not_expr<'a>() -> Parser<'a, char, Expr> {
+    ((tag("not.") | tag("!"))
+        * ((sym('(') * call(filter_expr) - sym(')'))
+            .map(|expr| Expr::Nested(Box::new(expr)))
+            | call(binary_operation_expr)))
+    .map(|expr| {
+        Expr::UnaryOperation(Box::new(UnaryOperation {
+            operator: Operator::Not,
+            expr,
+        }))
+    })
+}
+
+/// `after=` with no operator is the cursor of the keyset pagination,
+/// which is not the shorthand of an equality with a column named `after`
+fn keyset_cursor<'a>() -> Parser<'a, char, ()> {
+    tag("after=").discard() - !(operator() - sym('.'))
+}
+
 fn binary_operation_expr<'a>() -> Parser<'a, char, Expr> {
-    (sym('(') * call(binary_operation_expr) - sym(')'))
-        .map(|expr| Expr::Nested(Box::new(expr)))
-        | (expr() - sym('=') + (operator() - sym('.')).opt() + expr()).map(
-            |((left, operator), right)| {
+    call(not_expr)
+        | (sym('(') * call(binary_operation_expr) - sym(')'))
+            .map(|expr| Expr::Nested(Box::new(expr)))
+        | (expr() - sym('=') - tag("is_null") - !is_a(alphanum_or_underscore))
+            .map(|left| {
                 Expr::BinaryOperation(Box::new(BinaryOperation {
                     left,
-                    operator: operator.unwrap_or(Operator::Eq),
-                    right,
+                    operator: Operator::IsNull,
+                    right: Expr::Value(Value::Null),
                 }))
-            },
-        )
+            })
+        | (!keyset_cursor() * expr() - sym('=')
+            + (operator() - sym('.')).opt()
+            + expr())
+        .map(|((left, operator), right)| {
+            Expr::BinaryOperation(Box::new(BinaryOperation {
+                left,
+                operator: operator.unwrap_or(Operator::Eq),
+                right,
+            }))
+        })
         | (simple_operation_expr() + connector() + call(binary_operation_expr))
             .map(|((left, operator), right)| {
                 Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -257,16 +373,40 @@ pub fn filter_expr<'a>() -> Parser<'a, char, Expr> {
 
 fn from_table<'a>() -> Parser<'a, char, FromTable> {
     (table().expect("Expecting a valid table name")
-        + (join_type() + call(from_table)))
-    .map(|(from, (join_type, from_table))| FromTable {
-        from,
-        join: Some((join_type, Box::new(from_table))),
-    }) | (table().expect("Expecting a valid table name")
-        + (join_type() + call(from_table)).opt())
-    .map(|(from, join)| FromTable {
+        + table_alias().opt()
+        + call(join).opt())
+    .map(|((from, alias), join)| FromTable {
         from,
-        join: join
-            .map(|(join_type, from_table)| (join_type, Box::new(from_table))),
+        alias,
+        join,
+        on: vec![],
+    })
+}
+
+/// =>manager
+fn table_alias<'a>() -> Parser<'a, char, String> {
+    tag("=>") * strict_ident()
+}
+
+/// the joined table with the optional alias and explicit join condition,
+/// ie: `<-users=>creator:created_by=user_id`
+fn join<'a>() -> Parser<'a, char, (JoinType, Box<FromTable>)> {
+    (join_type()
+        + table().expect("Expecting a valid table name")
+        + table_alias().opt()
+        + (sym(':') * list_fail(column() - sym('=') + column(), sym(',')))
+            .opt()
+        + call(join).opt())
+    .map(|((((join_type, from), alias), on), join)| {
+        (
+            join_type,
+            Box::new(FromTable {
+                from,
+                alias,
+                join,
+                on: on.unwrap_or_default(),
+            }),
+        )
     })
 }
 
@@ -304,51 +444,222 @@ fn limit<'a>() -> Parser<'a, char, Limit> {
     .map(|(limit, offset)| Limit { limit, offset })
 }
 
+/// a value of the cursor, where a value that is not quoted and
+/// not a number is a string up to the next `,` or `&`,
+/// ie: `2020-01-01T00:00:00`, a quoted string can have a `,` and
+/// escapes its quote, ie: `'O\'Brien, Jr.'`
+fn cursor_value<'a>() -> Parser<'a, char, Value> {
+    let value_end = -(one_of(",&").discard() | end_or_ln());
+    ((null()
+        | bool().map(Value::Bool)
+        | number()
+        | quoted_string().map(Value::String)
+        | single_quoted_string().map(Value::String))
+        - value_end)
+        | none_of(",&\n")
+            .repeat(1..)
+            .map(|v| Value::String(String::from_iter(v)))
+}
+
+/// after=2020-01-01T00:00:00,123&limit=50
+fn keyset<'a>() -> Parser<'a, char, Keyset> {
+    ((tag("after") - sym('='))
+        * list_fail(cursor_value(), sym(','))
+            .expect("Expecting values for after")
+        - sym('&')
+        + ((tag("limit") - sym('='))
+            * integer().expect("Expecting an integer value for limit"))
+        .expect("must specify a limit"))
+    .map(|(after, limit)| Keyset { after, limit })
+}
+
 fn range<'a>() -> Parser<'a, char, Range> {
-    page().map(Range::Page) | limit().map(Range::Limit)
+    page().map(Range::Page)
+        | limit().map(Range::Limit)
+        | keyset().map(Range::Keyset)
 }
 
 fn direction<'a>() -> Parser<'a, char, Direction> {
     tag("asc").map(|_| Direction::Asc) | tag("desc").map(|_| Direction::Desc)
 }
 
-/// height.asc
+fn nulls<'a>() -> Parser<'a, char, Nulls> {
+    tag("nullsfirst").map(|_| Nulls::First)
+        | tag("nullslast").map(|_| Nulls::Last)
+}
+
+/// height.asc, price.desc.nullslast
 fn order<'a>() -> Parser<'a, char, Order> {
-    (expr() + (sym('.') * direction()).opt())
-        .map(|(expr, direction)| Order { expr, direction })
+    (expr() + (sym('.') * direction()).opt() + (sym('.') * nulls()).opt()).map(
+        |((expr, direction), nulls)| Order {
+            expr,
+            direction,
+            nulls,
+        },
+    )
+}
+
+/// the end of a clause, which is either followed by the next clause or
+/// the end of the statement
+fn clause_end<'a>() -> Parser<'a, char, bool> {
+    -(sym('&').discard() | end_or_ln())
+}
+
+/// the end of a clause in a subquery, which is either followed by the next
+/// clause or the closing parenthesis of the subquery
+fn subquery_clause_end<'a>() -> Parser<'a, char, bool> {
+    -(sym('&').discard() | sym(')').discard())
 }
 
 /// person{name,age,class}?(age=gt.42&student=eq.true)|(gender=eq.M)&group_by=(age),grade,gender&having=min(age)=gte.42&order_by=age.desc,height.asc&page=2&page_size=10
 pub fn select<'a>() -> Parser<'a, char, Select> {
-    (from_table() + expr_projection().opt() - sym('?').opt()
-        + filter_expr().opt()
-        + (sym('&') * tag("group_by=") * list_fail(expr(), sym(','))).opt()
-        + (sym('&') * tag("having=") * filter_expr()).opt()
-        + (sym('&') * tag("order_by=") * list_fail(call(order), sym(',')))
-            .opt()
-        + (sym('&') * range()).opt()
-        - end_or_ln())
+    select_clauses(clause_end) - end_or_ln()
+}
+
+/// the select of a subquery that follows `(GET/` up to the closing
+/// parenthesis, ie: `(GET/users{user_id}?active=eq.true)`.
+/// The subquery is committed once the `(GET/` is matched
+pub(crate) fn subquery<'a>() -> Parser<'a, char, Select> {
+    (select_clauses(subquery_clause_end) - sym(')')).expect("subquery")
+}
+
+/// the alternatives that can be parsed in each clause of a select
+const TABLE_EXPECTED: &[&str] = &["table name"];
+const PROJECTION_EXPECTED: &[&str] =
+    &["column", "function", "`=>`", "`,`", "`}`"];
+pub(crate) const FILTER_EXPECTED: &[&str] =
+    &["column", "operator", "value", "`&`", "`|`", "`(`", "`)`"];
+const GROUP_BY_EXPECTED: &[&str] = &["column", "function", "`,`"];
+const HAVING_EXPECTED: &[&str] =
+    &["function", "operator", "value", "`&`", "`|`"];
+const ORDER_BY_EXPECTED: &[&str] = &["column", "`.asc`", "`.desc`", "`,`"];
+const RANGE_EXPECTED: &[&str] = &[
+    "`page=`",
+    "`&page_size=`",
+    "`limit=`",
+    "`&offset=`",
+    "`after=`",
+    "value",
+    "integer",
+];
+const WITH_EXPECTED: &[&str] = &["name", "`:`", "`(GET/`select`)`", "`,`"];
+
+/// the clauses of a select, where each clause ends with `clause_end`
+fn select_clauses<'a>(
+    clause_end: fn() -> Parser<'a, char, bool>,
+) -> Parser<'a, char, Select> {
+    (in_clause(Clause::Table, TABLE_EXPECTED, from_table())
+        + clause(
+            Clause::Projection,
+            PROJECTION_EXPECTED,
+            -sym('{'),
+            expr_projection(),
+        )
+        + (sym('?').opt()
+            * clause(
+                Clause::Filter,
+                FILTER_EXPECTED,
+                !clause_end(),
+                filter_expr() - clause_end(),
+            ))
+        + clause(
+            Clause::GroupBy,
+            GROUP_BY_EXPECTED,
+            sym('&') * tag("group_by="),
+            list_fail(expr(), sym(',')) - clause_end(),
+        )
+        + clause(
+            Clause::Having,
+            HAVING_EXPECTED,
+            sym('&') * tag("having="),
+            filter_expr() - clause_end(),
+        )
+        + clause(
+            Clause::OrderBy,
+            ORDER_BY_EXPECTED,
+            sym('&') * tag("order_by="),
+            list_fail(call(order), sym(',')) - clause_end(),
+        )
+        + clause(
+            Clause::Range,
+            RANGE_EXPECTED,
+            sym('&') * -(tag("page=") | tag("limit=") | tag("after=")),
+            range(),
+        )
+        + clause(
+            Clause::With,
+            WITH_EXPECTED,
+            sym('&') * tag("with="),
+            list_fail(cte(), sym(',')) - clause_end(),
+        ))
     .map(
         |(
             (
-                ((((from_table, projection), filter), group_by), having),
-                order_by,
+                (
+                    ((((from_table, projection), filter), group_by), having),
+                    order_by,
+                ),
+                range,
             ),
-            range,
+            with,
         )| {
+            let (distinct, projection) = match projection {
+                Some((distinct, projection)) => (distinct, Some(projection)),
+                None => (false, None),
+            };
             Select {
                 from_table,
                 filter,
                 group_by,
                 having,
                 projection,
+                distinct,
                 order_by,
                 range,
+                with,
             }
         },
     )
 }
 
+fn set_operator<'a>() -> Parser<'a, char, SetOperator> {
+    tag("union_all").map(|_| SetOperator::UnionAll)
+        | tag("union").map(|_| SetOperator::Union)
+        | tag("intersect").map(|_| SetOperator::Intersect)
+        | tag("except").map(|_| SetOperator::Except)
+}
+
+/// (GET/product{name})union(GET/service{name})&order_by=name&limit=10
+pub fn set_operation<'a>() -> Parser<'a, char, SetOperation> {
+    (tag("(GET/") * call(subquery)
+        + (set_operator() - tag("(GET/") + call(subquery)).repeat(1..)
+        + clause(
+            Clause::OrderBy,
+            ORDER_BY_EXPECTED,
+            sym('&') * tag("order_by="),
+            list_fail(call(order), sym(',')) - clause_end(),
+        )
+        + clause(
+            Clause::Range,
+            RANGE_EXPECTED,
+            sym('&') * -(tag("page=") | tag("limit=") | tag("after=")),
+            range(),
+        )
+        - end_or_ln())
+    .map(|(((select, operations), order_by), range)| SetOperation {
+        select,
+        operations,
+        order_by,
+        range,
+    })
+}
+
+/// top_product:(GET/product{name}?price=gt.100)
+fn cte<'a>() -> Parser<'a, char, Cte> {
+    (strict_ident() - sym(':') - tag("(GET/") + call(subquery))
+        .map(|(name, select)| Cte { name, select })
+}
+
 #[cfg(test)]
 mod test_private {
     use super::*;
@@ -377,11 +688,11 @@ mod test_private {
         println!("{:#?}", ret);
         assert_eq!(
             ret,
-            BinaryOperation {
-                left: Expr::Value(Value::Number(1.0)),
+            Expr::BinaryOperation(Box::new(BinaryOperation {
+                left: Expr::Value(Value::Integer(1)),
                 operator: Operator::Plus,
-                right: Expr::Value(Value::Number(1.0))
-            }
+                right: Expr::Value(Value::Integer(1))
+            }))
         );
     }
 
@@ -397,7 +708,7 @@ mod test_private {
             Expr::BinaryOperation(Box::new(BinaryOperation {
                 left: Expr::Column(ColumnName { name: "age".into() }),
                 operator: Operator::Gt,
-                right: Expr::Value(Value::Number(42.0))
+                right: Expr::Value(Value::Integer(42))
             }))
         );
     }
@@ -414,7 +725,7 @@ mod test_private {
                 Expr::BinaryOperation(Box::new(BinaryOperation {
                     left: Expr::Column(ColumnName { name: "age".into() }),
                     operator: Operator::Gt,
-                    right: Expr::Value(Value::Number(42.0))
+                    right: Expr::Value(Value::Integer(42))
                 }))
             ))))
         );
@@ -431,7 +742,7 @@ mod test_private {
                 left: Expr::BinaryOperation(Box::new(BinaryOperation {
                     left: Expr::Column(ColumnName { name: "age".into() }),
                     operator: Operator::Gt,
-                    right: Expr::Value(Value::Number(42.0))
+                    right: Expr::Value(Value::Integer(42))
                 })),
                 operator: Operator::Or,
                 right: Expr::Value(Value::Bool(true))
@@ -451,7 +762,7 @@ mod test_private {
                 right: Expr::BinaryOperation(Box::new(BinaryOperation {
                     left: Expr::Column(ColumnName { name: "age".into() }),
                     operator: Operator::Gt,
-                    right: Expr::Value(Value::Number(42.0))
+                    right: Expr::Value(Value::Integer(42))
                 })),
             }))
         );
@@ -470,7 +781,7 @@ mod test_private {
                     right: Expr::BinaryOperation(Box::new(BinaryOperation {
                         left: Expr::Column(ColumnName { name: "age".into() }),
                         operator: Operator::Gt,
-                        right: Expr::Value(Value::Number(42.0))
+                        right: Expr::Value(Value::Integer(42))
                     })),
                 }
             ))))
@@ -489,7 +800,7 @@ mod test_private {
                     BinaryOperation {
                         left: Expr::Column(ColumnName { name: "age".into() }),
                         operator: Operator::Gt,
-                        right: Expr::Value(Value::Number(42.0))
+                        right: Expr::Value(Value::Integer(42))
                     }
                 )))),
                 operator: Operator::Or,
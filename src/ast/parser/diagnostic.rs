@@ -0,0 +1,215 @@
+//! Diagnostic of a failed parse, pointing to the location in the input
+//! where the parse failed and what was expected at that location.
+use super::utils::Tag;
+use std::fmt;
+
+/// The part of the restq statement where the parse failed
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Clause {
+    Table,
+    Projection,
+    Filter,
+    GroupBy,
+    Having,
+    OrderBy,
+    Range,
+    With,
+    ColumnDef,
+    ColumnValues,
+}
+
+/// A structured parse error
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    /// the input that is parsed
+    pub input: String,
+    /// the char offset in the input where the parse failed
+    pub position: usize,
+    /// the clause that is being parsed when the error occured
+    pub clause: Option<Clause>,
+    /// the alternatives that could have been parsed at the position
+    pub expected: Vec<String>,
+}
+
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Clause::Table => write!(f, "table"),
+            Clause::Projection => write!(f, "projection"),
+            Clause::Filter => write!(f, "filter"),
+            Clause::GroupBy => write!(f, "group_by"),
+            Clause::Having => write!(f, "having"),
+            Clause::OrderBy => write!(f, "order_by"),
+            Clause::Range => write!(f, "range"),
+            Clause::With => write!(f, "with"),
+            Clause::ColumnDef => write!(f, "column_def"),
+            Clause::ColumnValues => write!(f, "column_values"),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// create a diagnostic from the error of parsing this input,
+    /// the innermost clause tagged in the error and the farthest position in the error is used
+    pub(crate) fn new(
+        input: &[char],
+        error: &pom::Error,
+        tags: &[Tag],
+    ) -> Self {
+        let mut tag = None;
+        let mut position = 0;
+        Self::walk(input, error, tags, &mut tag, &mut position);
+        Diagnostic {
+            input: input.iter().collect(),
+            position,
+            clause: tag.map(|tag| tag.clause),
+            expected: tag
+                .map(|tag| tag.expected.iter().map(|e| e.to_string()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn walk<'t>(
+        input: &[char],
+        error: &pom::Error,
+        tags: &'t [Tag],
+        tag: &mut Option<&'t Tag>,
+        position: &mut usize,
+    ) {
+        if let Some(found) = tags.iter().find(|tag| tag.error == *error) {
+            *tag = Some(found);
+        }
+        match error {
+            pom::Error::Incomplete => *position = input.len(),
+            pom::Error::Mismatch { position: pos, .. }
+            | pom::Error::Conversion { position: pos, .. } => {
+                *position = (*position).max(*pos)
+            }
+            pom::Error::Expect {
+                position: pos,
+                inner,
+                ..
+            } => {
+                *position = (*position).max(*pos);
+                Self::walk(input, inner, tags, tag, position);
+            }
+            pom::Error::Custom {
+                position: pos,
+                inner,
+                ..
+            } => {
+                *position = (*position).max(*pos);
+                if let Some(inner) = inner {
+                    Self::walk(input, inner, tags, tag, position);
+                }
+            }
+        }
+    }
+
+    /// the character found at the position, None if it is at the end
+    pub fn found(&self) -> Option<char> {
+        self.input.chars().nth(self.position)
+    }
+
+    /// the byte offset of the position in the input
+    pub fn byte_offset(&self) -> usize {
+        self.input
+            .char_indices()
+            .nth(self.position)
+            .map(|(offset, _)| offset)
+            .unwrap_or_else(|| self.input.len())
+    }
+
+    /// display the line of the input where the error occured
+    /// with a caret pointing to the position
+    ///
+    /// ```text
+    /// person{name,age?age=gt.
+    ///                ^ unexpected `?` in projection, expected one of: ...
+    /// ```
+    pub fn render(&self) -> String {
+        let offset = self.byte_offset();
+        let line_start =
+            self.input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = self.input[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or_else(|| self.input.len());
+        let column = self.input[line_start..offset].chars().count();
+        format!(
+            "{}\n{}^ {}",
+            &self.input[line_start..line_end],
+            " ".repeat(column),
+            self
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found() {
+            Some(ch) => write!(f, "unexpected `{}`", ch)?,
+            None => write!(f, "unexpected end of input")?,
+        }
+        write!(f, " at {}", self.position)?;
+        if let Some(clause) = &self.clause {
+            write!(f, " in {}", clause)?;
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected one of: {}", self.expected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::{ddl::table_def, parser::utils::parse_diagnosed},
+        parser::select,
+        to_chars,
+    };
+
+    fn diagnose(input: &str) -> Diagnostic {
+        let input = to_chars(input);
+        parse_diagnosed(select(), &input).expect_err("must fail")
+    }
+
+    #[test]
+    fn unclosed_projection() {
+        let diagnostic = diagnose("person{name,age?age=gt.");
+        assert_eq!(diagnostic.position, 15);
+        assert_eq!(diagnostic.clause, Some(Clause::Projection));
+        assert_eq!(diagnostic.found(), Some('?'));
+        assert_eq!(
+            diagnostic.render(),
+            "person{name,age?age=gt.\n               ^ unexpected `?` at 15 in projection, expected one of: column, function, `=>`, `,`, `}`"
+        );
+    }
+
+    #[test]
+    fn unbalanced_filter() {
+        let diagnostic = diagnose("person{name,age}?age=gt.42)");
+        assert_eq!(diagnostic.clause, Some(Clause::Filter));
+        assert_eq!(diagnostic.position, 26);
+        assert_eq!(diagnostic.found(), Some(')'));
+    }
+
+    #[test]
+    fn invalid_range() {
+        let diagnostic = diagnose("person?age=gt.42&page=x&page_size=10");
+        assert_eq!(diagnostic.clause, Some(Clause::Range));
+        assert_eq!(diagnostic.position, 22);
+    }
+
+    #[test]
+    fn invalid_column_def() {
+        let input = to_chars("product{*product_id:s32,name text}");
+        let diagnostic =
+            parse_diagnosed(table_def(), &input).expect_err("must fail");
+        assert_eq!(diagnostic.clause, Some(Clause::ColumnDef));
+        assert_eq!(diagnostic.position, 28);
+        assert_eq!(diagnostic.byte_offset(), 28);
+    }
+}
@@ -2,9 +2,10 @@ use super::*;
 use crate::{
     ast::{
         ddl::{ColumnAttribute, ColumnDef, DataTypeDef, Foreign, TableDef},
-        ColumnName, TableName,
+        parser::utils::parse_diagnosed,
+        ColumnName, TableError, TableLookup, TableName,
     },
-    DataType,
+    DataType, DataValue, Dialect,
 };
 
 #[test]
@@ -67,6 +68,8 @@ fn test_select_with_no_selection() {
                     name: "product".into()
                 },
                 join: None,
+                on: vec![],
+                alias: None,
             },
             ..Default::default()
         }
@@ -91,8 +94,12 @@ fn test_right_join_table() {
                             name: "product_old".into()
                         },
                         join: None,
+                        on: vec![],
+                        alias: None,
                     })
                 )),
+                on: vec![],
+                alias: None,
             },
             ..Default::default()
         }
@@ -210,7 +217,9 @@ fn test_expr_with_renames2() {
 #[test]
 fn test_expr_list() {
     let input = to_chars("{column1,column2,column3}");
-    let ret = expr_projection().parse(&input).expect("must be parsed");
+    let (distinct, ret) =
+        expr_projection().parse(&input).expect("must be parsed");
+    assert!(!distinct);
     println!("{:#?}", ret);
     assert_eq!(
         ret,
@@ -294,7 +303,9 @@ fn test_strict_column_fail() {
 fn test_simple_select_to_sql() {
     let input = to_chars("person{name,age,class}");
     let ret = select().parse(&input).expect("must be parsed");
-    let select = ret.into_sql_select(None).expect("must not fail");
+    let select = ret
+        .into_sql_select(None, &Dialect::PostgreSql)
+        .expect("must not fail");
     println!("{}", select);
     assert_eq!(select.to_string(), "SELECT name, age, class FROM person");
 }
@@ -303,7 +314,9 @@ fn test_simple_select_to_sql() {
 fn test_table_query_with_range_only() {
     let input = to_chars("person&page=1&page_size=20");
     let ret = select().parse(&input).expect("must be parsed");
-    let select = ret.into_sql_query(None).expect("must not fail");
+    let select = ret
+        .into_sql_query(None, &Dialect::PostgreSql)
+        .expect("must not fail");
     println!("{}", select);
     assert_eq!(
         select.to_string(),
@@ -312,11 +325,400 @@ fn test_table_query_with_range_only() {
     );
 }
 
+fn select_to_sql(input: &str) -> Result<String, crate::Error> {
+    let input = to_chars(input);
+    let ret = select().parse(&input).expect("must be parsed");
+    assert_eq!(input.iter().collect::<String>(), ret.to_string());
+    Ok(ret
+        .into_sql_statement(None, &Dialect::PostgreSql)?
+        .to_string())
+}
+
+#[test]
+fn test_operators_to_sql() {
+    assert_eq!(
+        select_to_sql("person?id=not_in.[1,2]&name=in.['a','b']").unwrap(),
+        "SELECT * FROM person WHERE id NOT IN (1, 2) AND name IN ('a', 'b')"
+    );
+    assert_eq!(
+        select_to_sql("person?deleted=is.null&name=is_not.null").unwrap(),
+        "SELECT * FROM person WHERE deleted IS NULL AND name IS NOT NULL"
+    );
+    assert_eq!(
+        select_to_sql("person?name=starts.'Jo'").unwrap(),
+        "SELECT * FROM person WHERE name ILIKE 'Jo%'"
+    );
+}
+
+#[test]
+fn test_new_operators_to_sql() {
+    assert_eq!(
+        select_to_sql("product?price=between.[10,20]").unwrap(),
+        "SELECT * FROM product WHERE price BETWEEN 10 AND 20"
+    );
+    assert_eq!(
+        select_to_sql("person?name=ends.'son'|name=contains.'foo'").unwrap(),
+        "SELECT * FROM person WHERE name ILIKE '%son' OR name ILIKE '%foo%'"
+    );
+    assert_eq!(
+        select_to_sql("person?deleted=is_null&is_nullable=eq.true").unwrap(),
+        "SELECT * FROM person WHERE deleted IS NULL AND is_nullable = true"
+    );
+    assert_eq!(
+        select_to_sql("person?a=is_distinct.b").unwrap(),
+        "SELECT * FROM person WHERE (a <> b OR a IS NULL OR b IS NULL) AND NOT (a IS NULL AND b IS NULL)"
+    );
+    assert_eq!(
+        select_to_sql("person?a=is_not_distinct.b").unwrap(),
+        "SELECT * FROM person WHERE NOT ((a <> b OR a IS NULL OR b IS NULL) AND NOT (a IS NULL AND b IS NULL))"
+    );
+}
+
+#[test]
+fn test_bare_word_is_the_pattern() {
+    assert_eq!(
+        select_to_sql("product?name=starts.Jo").unwrap(),
+        "SELECT * FROM product WHERE name ILIKE 'Jo%'"
+    );
+    assert_eq!(
+        select_to_sql("product?name=ends.son").unwrap(),
+        "SELECT * FROM product WHERE name ILIKE '%son'"
+    );
+    assert_eq!(
+        select_to_sql("product?name=contains.a_b").unwrap(),
+        "SELECT * FROM product WHERE strpos(LOWER(name), LOWER('a_b')) > 0"
+    );
+    assert_eq!(
+        select_to_sql("product?name=starts.'50%'").unwrap(),
+        "SELECT * FROM product WHERE LOWER(substr(name, 1, 3)) = LOWER('50%')"
+    );
+}
+
+#[test]
+fn test_between_needs_two_values() {
+    let err =
+        select_to_sql("product?price=between.[10]").expect_err("must fail");
+    assert!(matches!(err, crate::Error::UnsupportedOperation(_)));
+}
+
+#[test]
+fn test_negation() {
+    assert_eq!(
+        select_to_sql("person?not.(a=eq.1|b=eq.2)&c=eq.3").unwrap(),
+        "SELECT * FROM person WHERE NOT (a = 1 OR b = 2) AND c = 3"
+    );
+    assert_eq!(
+        select_to_sql("person?not.a=eq.1|b=eq.2").unwrap(),
+        "SELECT * FROM person WHERE NOT a = 1 OR b = 2"
+    );
+    assert_eq!(
+        select_to_sql("person?a=eq.1&not.(b=eq.2&not.(c=eq.3))").unwrap(),
+        "SELECT * FROM person WHERE a = 1 AND NOT (b = 2 AND NOT (c = 3))"
+    );
+}
+
+#[test]
+fn test_arithmetic_expressions() {
+    assert_eq!(
+        select_to_sql(
+            "product{name,price*quantity=>total}?price*quantity=gt.100&order_by=price*quantity.desc"
+        )
+        .unwrap(),
+        "SELECT name, price * quantity AS total FROM product WHERE price * quantity > 100 ORDER BY price * quantity DESC"
+    );
+    assert_eq!(
+        select_to_sql("product{(price+tax)*quantity}?price-discount%2=lt.10")
+            .unwrap(),
+        "SELECT (price + tax) * quantity FROM product WHERE price - discount % 2 < 10"
+    );
+}
+
+#[test]
+fn test_arithmetic_precedence() {
+    let input = to_chars("product?a+b*c-d/e=eq.1");
+    let ret = select().parse(&input).expect("must be parsed");
+    let binop = |left, operator, right| {
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left,
+            operator,
+            right,
+        }))
+    };
+    let column = |name: &str| Expr::Column(ColumnName { name: name.into() });
+    assert_eq!(
+        ret.filter,
+        Some(binop(
+            binop(
+                binop(
+                    column("a"),
+                    Operator::Plus,
+                    binop(column("b"), Operator::Multiply, column("c"))
+                ),
+                Operator::Minus,
+                binop(column("d"), Operator::Divide, column("e"))
+            ),
+            Operator::Eq,
+            Expr::Value(Value::Integer(1))
+        ))
+    );
+}
+
+#[test]
+fn test_subquery_in_filter() {
+    assert_eq!(
+        select_to_sql(
+            "product?created_by=in.(GET/users{user_id}?active=eq.true)"
+        )
+        .unwrap(),
+        "SELECT * FROM product WHERE created_by IN (SELECT user_id FROM users WHERE active = true)"
+    );
+    assert_eq!(
+        select_to_sql(
+            "product{name}?created_by=not_in.(GET/users{user_id}?active=eq.false&limit=10)&order_by=name"
+        )
+        .unwrap(),
+        "SELECT name FROM product WHERE created_by NOT IN (SELECT user_id FROM users WHERE active = false LIMIT 10) ORDER BY name"
+    );
+    assert_eq!(
+        select_to_sql("product?price=gt.(GET/product{avg(price)})").unwrap(),
+        "SELECT * FROM product WHERE price > (SELECT avg(price) FROM product)"
+    );
+}
+
+#[test]
+fn test_subquery_error_is_in_the_subquery() {
+    let input =
+        to_chars("product?created_by=in.(GET/users{user_id?active=eq.true)");
+    let diagnostic = parse_diagnosed(select(), &input).expect_err("must fail");
+    assert_eq!(diagnostic.clause, Some(Clause::Projection));
+    assert_eq!(diagnostic.position, 40);
+    assert_eq!(diagnostic.found(), Some('?'));
+}
+
+#[test]
+fn test_distinct() {
+    assert_eq!(
+        select_to_sql("person{distinct:city,state}?age=gt.18").unwrap(),
+        "SELECT DISTINCT city, state FROM person WHERE age > 18"
+    );
+    assert_eq!(
+        select_to_sql("person{state,count(distinct.city)=>cities}&group_by=state")
+            .unwrap(),
+        "SELECT state, count(DISTINCT city) AS cities FROM person GROUP BY state"
+    );
+}
+
+#[test]
+fn test_window_functions() {
+    assert_eq!(
+        select_to_sql(
+            "employee{name,row_number().over(partition_by=dept,order_by=salary.desc)=>rank}&order_by=row_number().over(order_by=salary.desc)"
+        )
+        .unwrap(),
+        "SELECT name, row_number() OVER (PARTITION BY dept ORDER BY salary DESC) AS rank FROM employee ORDER BY row_number() OVER (ORDER BY salary DESC)"
+    );
+    assert_eq!(
+        select_to_sql(
+            "employee{name,sum(salary).over(partition_by=dept,team)}"
+        )
+        .unwrap(),
+        "SELECT name, sum(salary) OVER (PARTITION BY dept, team) FROM employee"
+    );
+    assert_eq!(
+        select_to_sql("employee{count(id).over()}").unwrap(),
+        "SELECT count(id) OVER () FROM employee"
+    );
+}
+
+#[test]
+fn test_common_table_expressions() {
+    assert_eq!(
+        select_to_sql(
+            "top_product{name,total}?total=gt.100&order_by=total.desc&limit=10&with=top_product:(GET/product{name,price*quantity=>total}?is_active=eq.true)"
+        )
+        .unwrap(),
+        "WITH top_product AS (SELECT name, price * quantity AS total FROM product WHERE is_active = true) SELECT name, total FROM top_product WHERE total > 100 ORDER BY total DESC LIMIT 10"
+    );
+    assert_eq!(
+        select_to_sql(
+            "a{x}&with=a:(GET/b{x}?x=in.(GET/c{x})),c:(GET/d{x})"
+        )
+        .unwrap(),
+        "WITH a AS (SELECT x FROM b WHERE x IN (SELECT x FROM c)), c AS (SELECT x FROM d) SELECT x FROM a"
+    );
+}
+
+fn set_operation_to_sql(input: &str) -> String {
+    let input = to_chars(input);
+    let ret = set_operation().parse(&input).expect("must be parsed");
+    assert_eq!(input.iter().collect::<String>(), ret.to_string());
+    ret.into_sql_query(None, &Dialect::PostgreSql)
+        .expect("must not fail")
+        .to_string()
+}
+
+#[test]
+fn test_set_operations() {
+    assert_eq!(
+        set_operation_to_sql(
+            "(GET/product{name}?price=gt.100)union(GET/service{name})except(GET/archive{name})&order_by=name.desc&limit=10"
+        ),
+        "SELECT name FROM product WHERE price > 100 UNION SELECT name FROM service EXCEPT SELECT name FROM archive ORDER BY name DESC LIMIT 10"
+    );
+    assert_eq!(
+        set_operation_to_sql(
+            "(GET/a{x})union(GET/b{x})intersect(GET/c{x}&order_by=x&limit=5)"
+        ),
+        "(SELECT x FROM a UNION SELECT x FROM b) INTERSECT (SELECT x FROM c ORDER BY x LIMIT 5)"
+    );
+    let input = to_chars(
+        "(GET/a{x})union(GET/b{x})intersect(GET/c{x}&order_by=x&limit=5)",
+    );
+    let ret = set_operation().parse(&input).expect("must be parsed");
+    assert_eq!(
+        ret.into_sql_query(None, &Dialect::Sqlite)
+            .expect("must not fail")
+            .to_string(),
+        "SELECT * FROM (SELECT x FROM a UNION SELECT x FROM b) AS a INTERSECT SELECT * FROM (SELECT x FROM c ORDER BY x LIMIT 5) AS c"
+    );
+}
+
+#[test]
+fn test_explicit_join_condition() {
+    assert_eq!(
+        select_to_sql(
+            "product<-users:created_by=user_id{product.name,users.user_name}"
+        )
+        .unwrap(),
+        "SELECT product.name, users.user_name FROM product LEFT JOIN users ON product.created_by = users.user_id"
+    );
+    assert_eq!(
+        select_to_sql(
+            "product-><-users:created_by=user_id,org_id=org_id<-org:users.org_id=org.id"
+        )
+        .unwrap(),
+        "SELECT * FROM product JOIN users ON product.created_by = users.user_id AND product.org_id = users.org_id LEFT JOIN org ON users.org_id = org.id"
+    );
+}
+
+#[test]
+fn test_join_without_condition() {
+    let input = to_chars("product<-users");
+    let select = select().parse(&input).expect("must be parsed");
+    let err = select
+        .into_sql_statement(None, &Dialect::PostgreSql)
+        .expect_err("must fail");
+    assert!(matches!(
+        err,
+        crate::Error::TableError(TableError::NoSuppliedTableLookup)
+    ));
+
+    let mut table_lookup = TableLookup::new();
+    for input in ["product{*product_id:s32,name:text}", "users{*user_id:s32}"] {
+        let input = to_chars(input);
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+    }
+    let err = select
+        .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+        .expect_err("must fail");
+    assert!(matches!(
+        err,
+        crate::Error::TableError(TableError::NoJoinCondition(_, _))
+    ));
+}
+
+#[test]
+fn test_table_alias_and_self_join() {
+    assert_eq!(
+        select_to_sql(
+            "employee=>e<-employee=>manager:manager_id=employee_id{e.name,manager.name=>manager_name}"
+        )
+        .unwrap(),
+        "SELECT e.name, manager.name AS manager_name FROM employee AS e LEFT JOIN employee AS manager ON e.manager_id = manager.employee_id"
+    );
+
+    let mut table_lookup = TableLookup::new();
+    let input = to_chars(
+        "employee{*employee_id:s32,name:text,manager_id(employee::employee_id):s32?}",
+    );
+    table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+    let input = to_chars("employee=>e<-employee=>manager{e.name,manager.name}");
+    let select = select().parse(&input).expect("must be parsed");
+    assert_eq!(
+        select
+            .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+            .unwrap()
+            .to_string(),
+        "SELECT e.name, manager.name FROM employee AS e LEFT JOIN employee AS manager ON e.manager_id = manager.employee_id"
+    );
+}
+
+#[test]
+fn test_join_with_composite_foreign_key() {
+    let mut table_lookup = TableLookup::new();
+    for input in [
+        "order_line{*order_id:s32,*line_no:s32}",
+        "shipment{*shipment_id:s32,order_id:s32,line_no:s32,(order_id,line_no)(order_line)}",
+    ] {
+        let input = to_chars(input);
+        table_lookup.add_table(crate::table_def().parse(&input).unwrap());
+    }
+    let input = to_chars("shipment<-order_line{shipment_id}");
+    let select = select().parse(&input).expect("must be parsed");
+    assert_eq!(
+        select
+            .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
+            .unwrap()
+            .to_string(),
+        "SELECT shipment_id FROM shipment LEFT JOIN order_line ON shipment.order_id = order_line.order_id AND shipment.line_no = order_line.line_no"
+    );
+}
+
+#[test]
+fn test_negation_with_bang() {
+    let input = to_chars("person?!(a=eq.1|b=eq.2)");
+    let ret = select().parse(&input).expect("must be parsed");
+    assert_eq!(ret.to_string(), "person?not.(a=eq.1|b=eq.2)");
+    assert_eq!(
+        ret.filter,
+        Some(Expr::UnaryOperation(Box::new(UnaryOperation {
+            operator: Operator::Not,
+            expr: Expr::Nested(Box::new(Expr::BinaryOperation(Box::new(
+                BinaryOperation {
+                    left: Expr::BinaryOperation(Box::new(BinaryOperation {
+                        left: Expr::Column(ColumnName { name: "a".into() }),
+                        operator: Operator::Eq,
+                        right: Expr::Value(Value::Integer(1)),
+                    })),
+                    operator: Operator::Or,
+                    right: Expr::BinaryOperation(Box::new(BinaryOperation {
+                        left: Expr::Column(ColumnName { name: "b".into() }),
+                        operator: Operator::Eq,
+                        right: Expr::Value(Value::Integer(2)),
+                    })),
+                }
+            )))),
+        })))
+    );
+}
+
+#[test]
+fn test_unsupported_operation() {
+    let err = select_to_sql("person?active=is.true").expect_err("must fail");
+    assert!(matches!(err, crate::Error::UnsupportedOperation(_)));
+    assert_eq!(err.to_string(), "Unsupported operation: active=is.true");
+
+    let err = select_to_sql("person?age=starts.42").expect_err("must fail");
+    assert!(matches!(err, crate::Error::UnsupportedOperation(_)));
+}
+
 #[test]
 fn test_complex_query() {
     let input = to_chars("person{name,age,class}?(age=gt.42&student=eq.true)|(gender=eq.'M'&is_active=true)&group_by=sum(age),grade,gender&having=min(age)=gte.42&order_by=age.desc,height.asc&page=2&page_size=10");
     let ret = select().parse(&input).expect("must be parsed");
-    let select = ret.into_sql_query(None).expect("must not fail");
+    let select = ret
+        .into_sql_query(None, &Dialect::PostgreSql)
+        .expect("must not fail");
     println!("{}", select);
     assert_eq!(
         select.to_string(),
@@ -349,6 +751,7 @@ fn test_complex_query_with_join() {
             },
             foreign: None,
         }],
+        constraints: vec![],
     };
     let users_table = TableDef {
         table: TableName {
@@ -371,13 +774,14 @@ fn test_complex_query_with_join() {
                 column: Some(ColumnName { name: "id".into() }),
             }),
         }],
+        constraints: vec![],
     };
     let mut table_lookup = TableLookup::new(); //no table lookup
     table_lookup.add_table(person_table);
     table_lookup.add_table(users_table);
 
     let select = ret
-        .into_sql_query(Some(&table_lookup))
+        .into_sql_query(Some(&table_lookup), &Dialect::PostgreSql)
         .expect("must not fail");
 
     println!("ret: {}", select);
@@ -412,6 +816,7 @@ fn test_complex_query_with_multiple_join() {
             },
             foreign: None,
         }],
+        constraints: vec![],
     };
     let users_table = TableDef {
         table: TableName {
@@ -448,6 +853,7 @@ fn test_complex_query_with_multiple_join() {
                 }),
             },
         ],
+        constraints: vec![],
     };
     let student_table = TableDef {
         table: TableName {
@@ -472,6 +878,7 @@ fn test_complex_query_with_multiple_join() {
                 }),
             }),
         }],
+        constraints: vec![],
     };
     let mut table_lookup = TableLookup::new(); //no table lookup
     table_lookup.add_table(person_table);
@@ -479,7 +886,7 @@ fn test_complex_query_with_multiple_join() {
     table_lookup.add_table(student_table);
 
     let select = ret
-        .into_sql_query(Some(&table_lookup))
+        .into_sql_query(Some(&table_lookup), &Dialect::PostgreSql)
         .expect("must not fail");
 
     println!("ret: {}", select);
@@ -515,6 +922,7 @@ fn test_complex_query_with_multiple_join_full_join() {
             },
             foreign: None,
         }],
+        constraints: vec![],
     };
     let users_table = TableDef {
         table: TableName {
@@ -551,6 +959,7 @@ fn test_complex_query_with_multiple_join_full_join() {
                 }),
             },
         ],
+        constraints: vec![],
     };
     let student_table = TableDef {
         table: TableName {
@@ -575,6 +984,7 @@ fn test_complex_query_with_multiple_join_full_join() {
                 }),
             }),
         }],
+        constraints: vec![],
     };
     let mut table_lookup = TableLookup::new(); //no table lookup
     table_lookup.add_table(person_table);
@@ -582,7 +992,7 @@ fn test_complex_query_with_multiple_join_full_join() {
     table_lookup.add_table(student_table);
 
     let select = ret
-        .into_sql_query(Some(&table_lookup))
+        .into_sql_query(Some(&table_lookup), &Dialect::PostgreSql)
         .expect("must not fail");
 
     println!("ret: {}", select);
@@ -619,8 +1029,12 @@ fn test_simple_select() {
                             name: "users".into()
                         },
                         join: None,
+                        on: vec![],
+                        alias: None,
                     }),
                 ),),
+                on: vec![],
+                alias: None,
             },
             filter: Some(Expr::BinaryOperation(Box::new(BinaryOperation {
                 left: Expr::Nested(Box::new(Expr::BinaryOperation(Box::new(
@@ -631,7 +1045,7 @@ fn test_simple_select() {
                                     name: "age".into()
                                 },),
                                 operator: Operator::Gt,
-                                right: Expr::Value(Value::Number(42.0,),),
+                                right: Expr::Value(Value::Integer(42,),),
                             }
                         ),),
                         operator: Operator::And,
@@ -679,6 +1093,8 @@ fn test_simple_select() {
                     params: vec![Expr::Column(ColumnName {
                         name: "age".into()
                     },),],
+                    distinct: false,
+                    over: None,
                 },),
                 Expr::Column(ColumnName {
                     name: "grade".into()
@@ -693,9 +1109,11 @@ fn test_simple_select() {
                     params: vec![Expr::Column(ColumnName {
                         name: "age".into()
                     },),],
+                    distinct: false,
+                    over: None,
                 },),
                 operator: Operator::Gte,
-                right: Expr::Value(Value::Number(42.0,),),
+                right: Expr::Value(Value::Integer(42,),),
             }),),),
             projection: Some(vec![
                 ExprRename {
@@ -719,18 +1137,22 @@ fn test_simple_select() {
                 Order {
                     expr: Expr::Column(ColumnName { name: "age".into() },),
                     direction: Some(Direction::Desc,),
+                    nulls: None,
                 },
                 Order {
                     expr: Expr::Column(ColumnName {
                         name: "height".into()
                     },),
                     direction: Some(Direction::Asc,),
+                    nulls: None,
                 },
             ],),
             range: Some(Range::Page(Page {
                 page: 2,
                 page_size: 10,
             },),),
+            distinct: false,
+            with: None,
         }
     );
 }
@@ -739,7 +1161,9 @@ fn test_simple_select() {
 fn test_expr_selection() {
     let input =
         to_chars("{name,description,age,max(height)=>new_height,class}");
-    let ret = expr_projection().parse(&input).expect("must be parsed");
+    let (distinct, ret) =
+        expr_projection().parse(&input).expect("must be parsed");
+    assert!(!distinct);
     println!("{:#?}", ret);
     assert_eq!(
         ret,
@@ -765,7 +1189,9 @@ fn test_expr_selection() {
                     name: "max".into(),
                     params: vec![Expr::Column(ColumnName {
                         name: "height".into()
-                    })]
+                    })],
+                    distinct: false,
+                    over: None,
                 }),
                 rename: Some("new_height".to_string()),
             },
@@ -790,7 +1216,8 @@ fn test_order() {
             expr: Expr::Column(ColumnName {
                 name: "score".to_string()
             }),
-            direction: Some(Direction::Desc)
+            direction: Some(Direction::Desc),
+            nulls: None,
         }
     );
 }
@@ -848,6 +1275,195 @@ fn test_range_using_limit() {
     )
 }
 
+#[test]
+fn test_range_using_keyset() {
+    let input = to_chars("after=2020-01-01T00:00:00,'a,b',123&limit=50");
+    let ret = range().parse(&input).expect("must be parsed");
+    println!("{:#?}", ret);
+    assert_eq!(
+        ret,
+        Range::Keyset(Keyset {
+            after: vec![
+                Value::String("2020-01-01T00:00:00".into()),
+                Value::String("a,b".into()),
+                Value::Integer(123),
+            ],
+            limit: 50,
+        })
+    )
+}
+
+#[test]
+fn test_keyset_pagination() {
+    assert_eq!(
+        select_to_sql(
+            "product?is_active=eq.true|price=lt.10&order_by=created.desc,product_id.desc&after='2020-01-01T00:00:00',123&limit=50"
+        )
+        .unwrap(),
+        "SELECT * FROM product WHERE (is_active = true OR price < 10) AND (created, product_id) < ('2020-01-01T00:00:00', 123) ORDER BY created DESC, product_id DESC LIMIT 50"
+    );
+    assert_eq!(
+        select_to_sql("product&order_by=price&after=10&limit=50").unwrap(),
+        "SELECT * FROM product WHERE price > 10 ORDER BY price LIMIT 50"
+    );
+    assert_eq!(
+        select_to_sql(
+            "product&order_by=price.desc,name,product_id&after=10,'shovel',3&limit=50"
+        )
+        .unwrap(),
+        "SELECT * FROM product WHERE (price < 10 OR (price = 10 AND name > 'shovel') OR (price = 10 AND name = 'shovel' AND product_id > 3)) ORDER BY price DESC, name, product_id LIMIT 50"
+    );
+    assert!(matches!(
+        select_to_sql("product&order_by=price&after=10,3&limit=50"),
+        Err(crate::Error::InvalidCursor(_))
+    ));
+    assert!(matches!(
+        select_to_sql("product&after=10&limit=50"),
+        Err(crate::Error::InvalidCursor(_))
+    ));
+    let input = to_chars("product?after=10&limit=50");
+    assert!(select().parse(&input).is_err());
+    let input = to_chars("product&limit=50");
+    let mut ret = select().parse(&input).expect("must be parsed");
+    ret.range = Some(Range::Keyset(Keyset {
+        after: vec![],
+        limit: 50,
+    }));
+    assert!(matches!(
+        ret.into_sql_statement(None, &Dialect::PostgreSql),
+        Err(crate::Error::InvalidCursor(_))
+    ));
+}
+
+#[test]
+fn test_next_cursor() {
+    let input =
+        to_chars("product{name,created}&order_by=created.desc,product.name");
+    let ret = select().parse(&input).expect("must be parsed");
+    let columns = [
+        ColumnName {
+            name: "name".into(),
+        },
+        ColumnName {
+            name: "created".into(),
+        },
+    ];
+    let last_row = [
+        DataValue::Text("shovel".into()),
+        DataValue::I64(1_577_836_800),
+    ];
+    assert_eq!(ret.next_cursor(&columns, &last_row), None);
+
+    let input = to_chars(
+        "product{name,created}&order_by=created.desc,product.name&limit=10",
+    );
+    let mut select = select().parse(&input).expect("must be parsed");
+    let cursor = select.next_cursor(&columns, &last_row).unwrap();
+    select.range = Some(Range::Keyset(cursor));
+    assert_eq!(
+        select.to_string(),
+        "product{name,created}&order_by=created.desc,product.name&after=1577836800,'shovel'&limit=10"
+    );
+}
+
+#[test]
+fn test_next_cursor_matches_the_exact_column() {
+    let input = to_chars("a<-b{a.id,b.id}&order_by=b.id&limit=10");
+    let ret = select().parse(&input).expect("must be parsed");
+    let columns = [
+        ColumnName {
+            name: "a.id".into(),
+        },
+        ColumnName {
+            name: "b.id".into(),
+        },
+    ];
+    let last_row = [DataValue::I64(1), DataValue::I64(2)];
+    let cursor = ret.next_cursor(&columns, &last_row).unwrap();
+    assert_eq!(cursor.after, vec![Value::Integer(2)]);
+
+    let columns = [
+        ColumnName { name: "id".into() },
+        ColumnName { name: "id".into() },
+    ];
+    assert_eq!(ret.next_cursor(&columns, &last_row), None);
+    let input = to_chars("a<-b{a.id,b.id}&order_by=a.id&limit=10");
+    let ret = select().parse(&input).expect("must be parsed");
+    assert_eq!(ret.next_cursor(&columns, &last_row), None);
+    let columns = [
+        ColumnName { name: "id".into() },
+        ColumnName {
+            name: "name".into(),
+        },
+    ];
+    let cursor = ret.next_cursor(&columns, &last_row).unwrap();
+    assert_eq!(cursor.after, vec![Value::Integer(1)]);
+}
+
+#[test]
+fn test_next_cursor_with_quote_and_comma() {
+    let input = to_chars("person{name,id}&order_by=name,id&limit=10");
+    let mut ret = select().parse(&input).expect("must be parsed");
+    let columns = [
+        ColumnName {
+            name: "name".into(),
+        },
+        ColumnName { name: "id".into() },
+    ];
+    let last_row =
+        [DataValue::Text("O'Brien, Jr.\\".into()), DataValue::I64(7)];
+    let cursor = ret.next_cursor(&columns, &last_row).unwrap();
+    ret.range = Some(Range::Keyset(cursor.clone()));
+    let url = ret.to_string();
+    assert_eq!(
+        url,
+        r"person{name,id}&order_by=name,id&after='O\'Brien, Jr.\\',7&limit=10"
+    );
+    let input = to_chars(&url);
+    let parsed = select().parse(&input).expect("must be parsed");
+    assert_eq!(parsed.range, Some(Range::Keyset(cursor)));
+}
+
+#[test]
+fn test_order_with_nulls() {
+    let input = to_chars("price.nullsfirst");
+    let ret = order().parse(&input).expect("must be parsed");
+    assert_eq!(
+        ret,
+        Order {
+            expr: Expr::Column(ColumnName {
+                name: "price".into()
+            }),
+            direction: None,
+            nulls: Some(Nulls::First),
+        }
+    );
+    let input = to_chars("product.price.asc.nullslast");
+    let ret = order().parse(&input).expect("must be parsed");
+    assert_eq!(ret.to_string(), "product.price.asc.nullslast");
+}
+
+#[test]
+fn test_clause_keywords_are_column_names() {
+    assert_eq!(
+        select_to_sql("person?with=eq.1").unwrap(),
+        "SELECT * FROM person WHERE with = 1"
+    );
+    assert_eq!(
+        select_to_sql("person?after=eq.1").unwrap(),
+        "SELECT * FROM person WHERE after = 1"
+    );
+    assert_eq!(
+        select_to_sql("person?returning=eq.1").unwrap(),
+        "SELECT * FROM person WHERE returning = 1"
+    );
+    assert_eq!(
+        select_to_sql("person?person.nullsfirst=eq.true&order_by=person.nullslast.nullsfirst")
+            .unwrap(),
+        "SELECT * FROM person WHERE person.nullsfirst = true ORDER BY person.nullslast IS NOT NULL, person.nullslast"
+    );
+}
+
 #[test]
 fn test_page_with_size() {
     let input = to_chars("page=2&page_size=10");
@@ -936,8 +1552,12 @@ fn test_from_right_join() {
                         name: "users".into()
                     },
                     join: None,
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -960,8 +1580,12 @@ fn test_from_left_join() {
                         name: "users".into()
                     },
                     join: None,
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -989,11 +1613,17 @@ fn test_from_multiple_left_join() {
                             from: TableName {
                                 name: "review".into()
                             },
-                            join: None
+                            join: None,
+                            on: vec![],
+                            alias: None,
                         })
                     )),
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -1016,8 +1646,12 @@ fn test_from_inner_join() {
                         name: "users".into()
                     },
                     join: None,
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -1040,8 +1674,12 @@ fn test_from_full_join() {
                         name: "users".into()
                     },
                     join: None,
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -1070,10 +1708,16 @@ fn test_from_table() {
                                 name: "customer".into()
                             },
                             join: None,
+                            on: vec![],
+                            alias: None,
                         }),
                     ),),
+                    on: vec![],
+                    alias: None,
                 }),
             ),),
+            on: vec![],
+            alias: None,
         }
     );
 }
@@ -1092,7 +1736,7 @@ fn test_more_complex_filter2() {
                     left: Expr::BinaryOperation(Box::new(BinaryOperation {
                         left: Expr::Column(ColumnName { name: "age".into() }),
                         operator: Operator::Gt,
-                        right: Expr::Value(Value::Number(42.0))
+                        right: Expr::Value(Value::Integer(42))
                     })),
                     operator: Operator::And,
                     right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1141,7 +1785,7 @@ fn test_complex_filter_with_many_parens() {
                     left: Expr::BinaryOperation(Box::new(BinaryOperation {
                         left: Expr::Column(ColumnName { name: "age".into() }),
                         operator: Operator::Gt,
-                        right: Expr::Value(Value::Number(42.0))
+                        right: Expr::Value(Value::Integer(42))
                     })),
                     operator: Operator::And,
                     right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1180,7 +1824,7 @@ fn test_complex_filter1() {
                     left: Expr::BinaryOperation(Box::new(BinaryOperation {
                         left: Expr::Column(ColumnName { name: "age".into() }),
                         operator: Operator::Gt,
-                        right: Expr::Value(Value::Number(42.0))
+                        right: Expr::Value(Value::Integer(42))
                     })),
                     operator: Operator::And,
                     right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1245,7 +1889,7 @@ fn test_filter_simple_filter_with_group_by() {
                 left: Expr::BinaryOperation(Box::new(BinaryOperation {
                     left: Expr::Column(ColumnName { name: "age".into() }),
                     operator: Operator::Gt,
-                    right: Expr::Value(Value::Number(42.0))
+                    right: Expr::Value(Value::Integer(42))
                 })),
                 operator: Operator::And,
                 right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1270,7 +1914,7 @@ fn test_filter_simple_filter_and() {
             left: Expr::BinaryOperation(Box::new(BinaryOperation {
                 left: Expr::Column(ColumnName { name: "age".into() }),
                 operator: Operator::Gt,
-                right: Expr::Value(Value::Number(42.0))
+                right: Expr::Value(Value::Integer(42))
             })),
             operator: Operator::And,
             right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1293,7 +1937,7 @@ fn test_filter_simple_filter_one() {
         Expr::BinaryOperation(Box::new(BinaryOperation {
             left: Expr::Column(ColumnName { name: "age".into() }),
             operator: Operator::Gt,
-            right: Expr::Value(Value::Number(42.0))
+            right: Expr::Value(Value::Integer(42))
         })),
     );
 }
@@ -1307,7 +1951,7 @@ fn test_filter_simple_filter_one_grouped() {
             BinaryOperation {
                 left: Expr::Column(ColumnName { name: "age".into() }),
                 operator: Operator::Gt,
-                right: Expr::Value(Value::Number(42.0))
+                right: Expr::Value(Value::Integer(42))
             }
         )))),
     );
@@ -1324,7 +1968,7 @@ fn test_filter_simple_filter_2_grouped() {
                 BinaryOperation {
                     left: Expr::Column(ColumnName { name: "age".into() }),
                     operator: Operator::Gt,
-                    right: Expr::Value(Value::Number(42.0))
+                    right: Expr::Value(Value::Integer(42))
                 }
             )))),
             operator: Operator::Or,
@@ -1351,7 +1995,7 @@ fn test_filter_simple_filter_or() {
             left: Expr::BinaryOperation(Box::new(BinaryOperation {
                 left: Expr::Column(ColumnName { name: "age".into() }),
                 operator: Operator::Gt,
-                right: Expr::Value(Value::Number(42.0))
+                right: Expr::Value(Value::Integer(42))
             })),
             operator: Operator::Or,
             right: Expr::BinaryOperation(Box::new(BinaryOperation {
@@ -1376,7 +2020,7 @@ fn test_condition_gt() {
         Expr::BinaryOperation(Box::new(BinaryOperation {
             left: Expr::Column(ColumnName { name: "age".into() }),
             operator: Operator::Gt,
-            right: Expr::Value(Value::Number(42.0))
+            right: Expr::Value(Value::Integer(42))
         }))
     );
 }
@@ -1391,7 +2035,7 @@ fn test_condition_lte() {
         Expr::BinaryOperation(Box::new(BinaryOperation {
             left: Expr::Column(ColumnName { name: "age".into() }),
             operator: Operator::Lte,
-            right: Expr::Value(Value::Number(42.0))
+            right: Expr::Value(Value::Integer(42))
         }))
     );
 }
@@ -1407,7 +2051,7 @@ fn test_condition_default_eq() {
         Expr::BinaryOperation(Box::new(BinaryOperation {
             left: Expr::Column(ColumnName { name: "age".into() }),
             operator: Operator::Eq,
-            right: Expr::Value(Value::Number(42.0))
+            right: Expr::Value(Value::Integer(42))
         }))
     );
 }
@@ -1422,7 +2066,9 @@ fn test_function() {
             name: "max".into(),
             params: vec![Expr::Column(ColumnName {
                 name: "seq_no".into()
-            })]
+            })],
+            distinct: false,
+            over: None,
         }
     );
 }
@@ -1437,7 +2083,9 @@ fn test_function_expr() {
             name: "max".into(),
             params: vec![Expr::Column(ColumnName {
                 name: "seq_no".into()
-            })]
+            })],
+            distinct: false,
+            over: None,
         })
     );
 }
@@ -1470,13 +2118,38 @@ fn test_value_bool2() {
 fn test_value_number() {
     let input = to_chars("0.1312312");
     let ret = value().parse(&input).expect("must be parsed");
-    assert_eq!(ret, Value::Number(0.1312312));
+    assert_eq!(ret, Value::Decimal("0.1312312".into()));
 }
 #[test]
 fn test_value_number2() {
     let input = to_chars("3.14159");
     let ret = value().parse(&input).expect("must be parsed");
-    assert_eq!(ret, Value::Number(3.14159));
+    assert_eq!(ret, Value::Decimal("3.14159".into()));
+}
+
+#[test]
+fn test_value_integer() {
+    let input = to_chars("9007199254740993");
+    let ret = value().parse(&input).expect("must be parsed");
+    assert_eq!(ret, Value::Integer(9007199254740993));
+    assert_eq!(ret.to_string(), "9007199254740993");
+}
+
+#[test]
+fn test_value_number_kinds() {
+    fn parse(s: &str) -> Value {
+        let input = to_chars(s);
+        let ret = value().parse(&input).expect("must be parsed");
+        ret
+    }
+    assert_eq!(parse("-42"), Value::Integer(-42));
+    assert_eq!(parse("1.0"), Value::Decimal("1.0".into()));
+    assert_eq!(parse("1.0").to_string(), "1.0");
+    assert_eq!(
+        parse("18446744073709551615"),
+        Value::Decimal("18446744073709551615".into())
+    );
+    assert_eq!(parse("1e3"), Value::Number(1000.0));
 }
 
 #[test]
@@ -1510,7 +2183,7 @@ fn test_null_expr() {
 fn test_value_value() {
     let input = to_chars("91.56");
     let ret = value().parse(&input).expect("must be parsed");
-    assert_eq!(ret, Value::Number(91.56));
+    assert_eq!(ret, Value::Decimal("91.56".into()));
 }
 
 #[test]
@@ -1,9 +1,50 @@
-use pom::parser::*;
+use super::diagnostic::{Clause, Diagnostic};
+use crate::ast::Value;
+use pom::{parser::*, Error};
 use std::{
+    cell::RefCell,
     iter::FromIterator,
     str::{self, FromStr},
 };
 
+/// The clause which tagged an error of the parser,
+/// with the alternatives that could have been parsed in that clause
+pub(crate) struct Tag {
+    pub error: Error,
+    pub clause: Clause,
+    pub expected: &'static [&'static str],
+}
+
+thread_local! {
+    /// the tags of the errors returned by the clause combinators,
+    /// only recorded while a diagnosed parse is running
+    static TAGS: RefCell<Option<Vec<Tag>>> = const { RefCell::new(None) };
+}
+
+/// Records the tags while it is alive, the tags of an enclosing
+/// diagnosed parse are restored when it is dropped
+struct Recording {
+    outer: Option<Vec<Tag>>,
+}
+
+impl Recording {
+    fn start() -> Self {
+        let outer = TAGS.with(|tags| tags.replace(Some(vec![])));
+        Recording { outer }
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        TAGS.with(|tags| tags.borrow_mut().replace(vec![]))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        TAGS.with(|tags| *tags.borrow_mut() = self.outer.take());
+    }
+}
+
 /// Parses a list with the defined separator, but will fail early when one of the
 /// item can not be parsed
 pub fn list_fail<'a, I, O, U>(
@@ -53,6 +94,138 @@ where
     })
 }
 
+/// Parses an optional clause which starts with the `prefix`.
+/// When the prefix does not match, the clause is skipped.
+/// Once the prefix matches the clause is committed, and the error
+/// in parsing the `body` is returned, tagged with the clause.
+pub(crate) fn clause<'a, P, O>(
+    clause: Clause,
+    expected: &'static [&'static str],
+    prefix: Parser<'a, char, P>,
+    body: Parser<'a, char, O>,
+) -> Parser<'a, char, Option<O>>
+where
+    P: 'a,
+    O: 'a,
+{
+    Parser::new(move |input: &'a [char], start: usize| {
+        match (prefix.method)(input, start) {
+            Ok((_, pos)) => match (body.method)(input, pos) {
+                Ok((out, end)) => Ok((Some(out), end)),
+                Err(e) => Err(tag_error(clause, expected, pos, e)),
+            },
+            Err(_) => Ok((None, start)),
+        }
+    })
+}
+
+/// tag the error of this parser with the clause
+pub(crate) fn in_clause<'a, O>(
+    clause: Clause,
+    expected: &'static [&'static str],
+    parser: Parser<'a, char, O>,
+) -> Parser<'a, char, O>
+where
+    O: 'a,
+{
+    Parser::new(move |input: &'a [char], start: usize| {
+        (parser.method)(input, start)
+            .map_err(|e| tag_error(clause, expected, start, e))
+    })
+}
+
+/// an `Expect` error is kept as is, so it will not be
+/// ignored by the succeeding alternatives.
+/// The tagged error is recorded with the clause and the expected alternatives,
+/// so the diagnostic don't have to read them back from the error message
+fn tag_error(
+    clause: Clause,
+    expected: &'static [&'static str],
+    position: usize,
+    error: Error,
+) -> Error {
+    let error = match error {
+        Error::Expect { .. } => Error::Expect {
+            message: clause.to_string(),
+            position,
+            inner: Box::new(error),
+        },
+        _ => Error::Custom {
+            message: clause.to_string(),
+            position,
+            inner: Some(Box::new(error)),
+        },
+    };
+    TAGS.with(|tags| {
+        if let Some(tags) = tags.borrow_mut().as_mut() {
+            tags.push(Tag {
+                error: error.clone(),
+                clause,
+                expected,
+            })
+        }
+    });
+    error
+}
+
+/// Parse the input with any of the parsers, ie: `select()`, `table_def()`
+/// or `insert()`, diagnosing the error with the clause where it failed
+/// and the alternatives that were expected there
+pub fn parse_diagnosed<'a, O>(
+    parser: Parser<'a, char, O>,
+    input: &'a [char],
+) -> Result<O, Diagnostic> {
+    let recording = Recording::start();
+    let result = parser.parse(input);
+    let tags = recording.tags();
+    result.map_err(|e| Diagnostic::new(input, &e, &tags))
+}
+
+/// Try each of the parsers in order, when all of them fail the error
+/// which got the farthest into the input is returned, unlike `|`
+/// which returns the error of the last parser.
+/// An `Expect` error is returned right away, the same as in `|`
+pub(crate) fn farthest<'a, O>(
+    parsers: Vec<Parser<'a, char, O>>,
+) -> Parser<'a, char, O>
+where
+    O: 'a,
+{
+    Parser::new(move |input: &'a [char], start: usize| {
+        let mut farthest: Option<(usize, Error)> = None;
+        for parser in parsers.iter() {
+            match (parser.method)(input, start) {
+                Ok(ret) => return Ok(ret),
+                Err(e @ Error::Expect { .. }) => return Err(e),
+                Err(e) => {
+                    let position = error_position(input, &e);
+                    if farthest.as_ref().is_none_or(|(p, _)| position > *p) {
+                        farthest = Some((position, e));
+                    }
+                }
+            }
+        }
+        Err(farthest.map(|(_, e)| e).unwrap_or(Error::Incomplete))
+    })
+}
+
+/// the farthest position in the input reached by the error
+fn error_position(input: &[char], error: &Error) -> usize {
+    match error {
+        Error::Incomplete => input.len(),
+        Error::Mismatch { position, .. }
+        | Error::Conversion { position, .. } => *position,
+        Error::Expect {
+            position, inner, ..
+        } => (*position).max(error_position(input, inner)),
+        Error::Custom {
+            position, inner, ..
+        } => inner.as_ref().map_or(*position, |inner| {
+            (*position).max(error_position(input, inner))
+        }),
+    }
+}
+
 pub fn to_chars(input: &str) -> Vec<char> {
     input.chars().collect()
 }
@@ -85,7 +258,8 @@ pub fn space<'a>() -> Parser<'a, char, ()> {
 }
 
 /// a number including decimal
-pub(crate) fn number<'a>() -> Parser<'a, char, f64> {
+/// integer, decimal or a number in exponent notation
+pub(crate) fn number<'a>() -> Parser<'a, char, Value> {
     let integer =
         one_of("123456789") - one_of("0123456789").repeat(0..) | sym('0');
     let frac = sym('.') + one_of("0123456789").repeat(1..);
@@ -95,7 +269,7 @@ pub(crate) fn number<'a>() -> Parser<'a, char, f64> {
     number
         .collect()
         .map(String::from_iter)
-        .convert(|s| f64::from_str(&s))
+        .convert(|s| Value::from_number_literal(&s))
 }
 
 pub(crate) fn integer<'a>() -> Parser<'a, char, i64> {
@@ -165,3 +339,21 @@ pub(crate) fn string<'a>() -> Parser<'a, char, String> {
     let string = char_string.repeat(0..);
     string.map(|strings| strings.concat())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::select;
+
+    #[test]
+    fn tags_are_only_recorded_in_a_diagnosed_parse() {
+        let input = to_chars("person{name,age?age=gt.");
+        assert!(select().parse(&input).is_err());
+        assert!(TAGS.with(|tags| tags.borrow().is_none()));
+
+        let diagnostic =
+            parse_diagnosed(select(), &input).expect_err("must fail");
+        assert_eq!(diagnostic.clause, Some(Clause::Projection));
+        assert!(TAGS.with(|tags| tags.borrow().is_none()));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    ast::{into_sql_order_by, Order, Range, Select, TableLookup},
+    Dialect, Error,
+};
+use serde::{Deserialize, Serialize};
+use sql_ast::ast as sql;
+use std::fmt;
+
+/// selects combined with set operators, the order_by and range
+/// is applied to the combined result,
+/// ie: `(GET/product{name})union(GET/service{name})&order_by=name&limit=10`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SetOperation {
+    pub select: Select,
+    pub operations: Vec<(SetOperator, Select)>,
+    pub order_by: Option<Vec<Order>>,
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum SetOperator {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+impl SetOperation {
+    pub fn into_sql_query(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<sql::Query, Error> {
+        let mut body = into_sql_set_expr(&self.select, table_lookup, dialect)?;
+        let alias = self.select.from_table.reference();
+        for (operator, select) in self.operations.iter() {
+            // intersect takes precedence over union and except in sql,
+            // so the preceding operations is enclosed in a parenthesis
+            // to keep them evaluated from left to right
+            let left = match body {
+                sql::SetExpr::SetOperation { .. }
+                    if *operator == SetOperator::Intersect =>
+                {
+                    nested_query(query_with_body(body), alias, dialect)
+                }
+                _ => body,
+            };
+            body = sql::SetExpr::SetOperation {
+                op: operator.into_sql_set_operator(),
+                all: *operator == SetOperator::UnionAll,
+                left: Box::new(left),
+                right: Box::new(into_sql_set_expr(
+                    select,
+                    table_lookup,
+                    dialect,
+                )?),
+            };
+        }
+        let mut query = query_with_body(body);
+        if let Some(order_by) = &self.order_by {
+            query.order_by = into_sql_order_by(order_by, dialect)?;
+        }
+        if let Some(Range::Keyset(_)) = &self.range {
+            return Err(Error::UnsupportedOperation(
+                "keyset pagination of set operations".to_string(),
+            ));
+        }
+        if let Some(range) = &self.range {
+            query.limit = Some(sql::Expr::Value(sql::Value::Number(
+                range.limit().to_string(),
+            )));
+            query.offset = range.offset().map(|offset| {
+                sql::Expr::Value(sql::Value::Number(offset.to_string()))
+            });
+        }
+        Ok(query)
+    }
+
+    pub fn into_sql_statement(
+        &self,
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<sql::Statement, Error> {
+        Ok(sql::Statement::Query(Box::new(
+            self.into_sql_query(table_lookup, dialect)?,
+        )))
+    }
+}
+
+/// a select with its own order_by, range or ctes is enclosed in a parenthesis
+fn into_sql_set_expr(
+    select: &Select,
+    table_lookup: Option<&TableLookup>,
+    dialect: &Dialect,
+) -> Result<sql::SetExpr, Error> {
+    if select.order_by.is_some()
+        || select.range.is_some()
+        || select.with.is_some()
+    {
+        Ok(nested_query(
+            select.into_sql_query(table_lookup, dialect)?,
+            select.from_table.reference(),
+            dialect,
+        ))
+    } else {
+        Ok(sql::SetExpr::Select(Box::new(
+            select.into_sql_select(table_lookup, dialect)?,
+        )))
+    }
+}
+
+/// the query in a parenthesis, or a select from the query as a subquery
+/// named `alias` when the database can not have a query in a parenthesis
+/// in a set operation, ie: `SELECT * FROM (SELECT x FROM c LIMIT 5) AS c`
+fn nested_query(
+    query: sql::Query,
+    alias: &str,
+    dialect: &Dialect,
+) -> sql::SetExpr {
+    if dialect.supports_nested_set_operations() {
+        return sql::SetExpr::Query(Box::new(query));
+    }
+    sql::SetExpr::Select(Box::new(sql::Select {
+        distinct: false,
+        projection: vec![sql::SelectItem::Wildcard],
+        from: vec![sql::TableWithJoins {
+            relation: sql::TableFactor::Derived {
+                lateral: false,
+                subquery: Box::new(query),
+                alias: Some(sql::TableAlias {
+                    name: sql::Ident::new(alias),
+                    columns: vec![],
+                }),
+            },
+            joins: vec![],
+        }],
+        selection: None,
+        group_by: vec![],
+        having: None,
+    }))
+}
+
+fn query_with_body(body: sql::SetExpr) -> sql::Query {
+    sql::Query {
+        ctes: vec![],
+        body,
+        order_by: vec![],
+        limit: None,
+        offset: None,
+        fetch: None,
+    }
+}
+
+impl SetOperator {
+    fn into_sql_set_operator(self) -> sql::SetOperator {
+        match self {
+            SetOperator::Union | SetOperator::UnionAll => {
+                sql::SetOperator::Union
+            }
+            SetOperator::Intersect => sql::SetOperator::Intersect,
+            SetOperator::Except => sql::SetOperator::Except,
+        }
+    }
+}
+
+impl fmt::Display for SetOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(GET/{})", self.select)?;
+        for (operator, select) in self.operations.iter() {
+            write!(f, "{}(GET/{})", operator, select)?;
+        }
+        if let Some(order_by) = &self.order_by {
+            write!(f, "&order_by=")?;
+            for (i, ord) in order_by.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                ord.fmt(f)?;
+            }
+        }
+        if let Some(range) = &self.range {
+            write!(f, "&")?;
+            range.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SetOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetOperator::Union => write!(f, "union"),
+            SetOperator::UnionAll => write!(f, "union_all"),
+            SetOperator::Intersect => write!(f, "intersect"),
+            SetOperator::Except => write!(f, "except"),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::ast::{ddl::TableDef, BinaryOperation, ColumnName, Expr, Operator};
+use crate::{
+    ast::{ddl::TableDef, BinaryOperation, ColumnName, Expr, Operator},
+    Dialect,
+};
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
 use std::{collections::BTreeMap, fmt};
@@ -10,12 +13,29 @@ pub enum TableError {
     NoSuppliedTableLookup,
     #[error("Table: `{0}` not found in the supplied TableLookup")]
     TableNotFound(String),
+    #[error("No join condition is specified and there is no foreign key between table: `{0}` and `{1}`")]
+    NoJoinCondition(String, String),
+    #[error("The foreign key columns does not match the primary columns of table: `{0}`")]
+    ForeignKeyMismatch(String),
+    #[error("Table: `{0}` has no primary key")]
+    NoPrimaryKey(String),
+    #[error("Column: `{1}` not found in table: `{0}`")]
+    ColumnNotFound(String, String),
+    #[error("Table: `{0}` has more than one primary key")]
+    DuplicatePrimaryKey(String),
 }
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
 pub struct FromTable {
     pub from: TableName,
+    /// the name used to refer to this table in the statement,
+    /// ie: `employee=>manager`
+    pub alias: Option<String>,
     pub join: Option<(JoinType, Box<FromTable>)>,
+    /// the explicit condition for joining this table to the preceding table,
+    /// each pair is the column of the preceding table and this table,
+    /// ie: `product<-users:created_by=user_id`
+    pub on: Vec<(ColumnName, ColumnName)>,
 }
 
 #[derive(
@@ -64,14 +84,19 @@ impl fmt::Display for TableName {
 }
 
 impl JoinType {
-    fn into_sql_join_operator(&self, constraint: Expr) -> sql::JoinOperator {
-        let on_constraint = sql::JoinConstraint::On(Into::into(&constraint));
-        match self {
+    fn into_sql_join_operator(
+        &self,
+        constraint: Expr,
+        dialect: &Dialect,
+    ) -> Result<sql::JoinOperator, crate::Error> {
+        let on_constraint =
+            sql::JoinConstraint::On(constraint.into_sql_expr(dialect)?);
+        Ok(match self {
             JoinType::InnerJoin => sql::JoinOperator::Inner(on_constraint),
             JoinType::LeftJoin => sql::JoinOperator::LeftOuter(on_constraint),
             JoinType::RightJoin => sql::JoinOperator::RightOuter(on_constraint),
             JoinType::FullJoin => sql::JoinOperator::FullOuter(on_constraint),
-        }
+        })
     }
 }
 
@@ -106,6 +131,11 @@ impl TableLookup {
     pub fn find_table(&self, table: &TableName) -> Option<&TableDef> {
         self.get_table_def(&table.name)
     }
+
+    /// all the table definitions, ordered by the table name
+    pub fn table_defs(&self) -> impl Iterator<Item = &TableDef> {
+        self.0.values()
+    }
 }
 
 impl FromTable {
@@ -113,143 +143,166 @@ impl FromTable {
     pub(crate) fn into_table_with_joins(
         &self,
         table_lookup: Option<&TableLookup>,
-    ) -> Result<sql::TableWithJoins, TableError> {
+        dialect: &Dialect,
+    ) -> Result<sql::TableWithJoins, crate::Error> {
         Ok(sql::TableWithJoins {
-            relation: Into::into(&self.from),
-            joins: self.maybe_extract_join(table_lookup)?,
+            relation: self.into_sql_table_factor(),
+            joins: self.extract_join(table_lookup, dialect)?,
         })
     }
 
-    fn maybe_extract_join(
-        &self,
-        table_lookup: Option<&TableLookup>,
-    ) -> Result<Vec<sql::Join>, TableError> {
-        match table_lookup {
-            Some(table_lookup) => self.extract_join(table_lookup),
-            None => match self.join {
-                Some(_) => Err(TableError::NoSuppliedTableLookup),
-                None => Ok(vec![]),
-            },
+    fn into_sql_table_factor(&self) -> sql::TableFactor {
+        sql::TableFactor::Table {
+            name: Into::into(&self.from),
+            alias: self.alias.as_ref().map(|alias| sql::TableAlias {
+                name: sql::Ident::new(alias),
+                columns: vec![],
+            }),
+            args: vec![],
+            with_hints: vec![],
         }
     }
 
+    /// the name used to qualify the columns of this table,
+    /// which is the alias if there is any
+    pub(crate) fn reference(&self) -> &str {
+        self.alias.as_ref().unwrap_or(&self.from.name)
+    }
+
     fn combine_expressions(
         binops: Vec<BinaryOperation>,
         operator: Operator,
     ) -> Expr {
         let mut iter = binops.into_iter();
-        let mut first = iter.next().expect("must have a first");
-        if let Some(next) = iter.next() {
-            first = BinaryOperation {
-                left: Expr::BinaryOperation(Box::new(first)),
-                operator: operator.clone(),
-                right: Expr::BinaryOperation(Box::new(next)),
-            };
-        }
-        Expr::BinaryOperation(Box::new(first))
+        let first = iter.next().expect("must have a first");
+        let combined = iter.fold(first, |left, right| BinaryOperation {
+            left: Expr::BinaryOperation(Box::new(left)),
+            operator: operator.clone(),
+            right: Expr::BinaryOperation(Box::new(right)),
+        });
+        Expr::BinaryOperation(Box::new(combined))
     }
 
-    /// If there is join definition, but no lookup table is supplied
-    /// it will return an error
+    /// The explicit join condition of the joined table is used when specified,
+    /// otherwise it is derived from the foreign keys in the table lookup.
+    /// An error is returned when there is no table lookup to derive it from
+    /// or there is no foreign key between the tables.
     fn extract_join(
         &self,
-        table_lookup: &TableLookup,
-    ) -> Result<Vec<sql::Join>, TableError> {
-        println!("extracting join from {:?}", self.from);
-        println!("join is {:#?}", self.join);
+        table_lookup: Option<&TableLookup>,
+        dialect: &Dialect,
+    ) -> Result<Vec<sql::Join>, crate::Error> {
         match &self.join {
             Some((join_type, joined_table)) => {
-                let joined_table_def =
-                    table_lookup.get_table_def(&joined_table.from.name);
-
-                let this_table_def =
-                    table_lookup.get_table_def(&self.from.name);
-
-                match (this_table_def, joined_table_def) {
-                    (None, _) => Err(TableError::TableNotFound(
-                        self.from.name.to_string(),
-                    )),
-                    (_, None) => Err(TableError::TableNotFound(
-                        joined_table.from.name.to_string(),
-                    )),
-                    (Some(this_table_def), Some(joined_table_def)) => {
-                        let pair1 = this_table_def
-                            .get_local_foreign_columns_pair_to_table(
-                                &joined_table_def.table,
-                            );
-                        println!("pair1: {:#?}", pair1);
-                        let pair2 = joined_table_def
-                            .get_local_foreign_columns_pair_to_table(
-                                &this_table_def.table,
-                            );
-                        println!("pair2: {:#?}", pair2);
-
-                        let mut joins = vec![];
-
-                        for (local_col, foreign_col) in pair1 {
-                            let local_complete_name = format!(
-                                "{}.{}",
-                                this_table_def.table.name, local_col.name
-                            );
-                            let referred_complete_name = format!(
-                                "{}.{}",
-                                joined_table_def.table.name, foreign_col.name
-                            );
-                            let binop = BinaryOperation {
-                                left: Expr::Column(ColumnName {
-                                    name: local_complete_name,
-                                }),
-                                operator: Operator::Eq,
-                                right: Expr::Column(ColumnName {
-                                    name: referred_complete_name,
-                                }),
-                            };
-                            joins.push(binop);
-                        }
+                let joins = if joined_table.on.is_empty() {
+                    let table_lookup = table_lookup
+                        .ok_or(TableError::NoSuppliedTableLookup)?;
+                    self.foreign_join_columns(table_lookup, joined_table)?
+                } else {
+                    joined_table
+                        .on
+                        .iter()
+                        .map(|(local_col, joined_col)| BinaryOperation {
+                            left: Expr::Column(qualified_column(
+                                self.reference(),
+                                local_col,
+                            )),
+                            operator: Operator::Eq,
+                            right: Expr::Column(qualified_column(
+                                joined_table.reference(),
+                                joined_col,
+                            )),
+                        })
+                        .collect()
+                };
+                let constraint =
+                    Self::combine_expressions(joins, Operator::And);
+                let mut ret = vec![sql::Join {
+                    relation: joined_table.into_sql_table_factor(),
+                    join_operator: join_type
+                        .into_sql_join_operator(constraint, dialect)?,
+                }];
+                ret.extend(joined_table.extract_join(table_lookup, dialect)?);
+                Ok(ret)
+            }
+            None => Ok(vec![]),
+        }
+    }
 
-                        for (local_col, foreign_col) in pair2 {
-                            let local_complete_name = format!(
-                                "{}.{}",
-                                joined_table_def.table.name, local_col.name
-                            );
-                            let referred_complete_name = format!(
-                                "{}.{}",
-                                this_table_def.table.name, foreign_col.name
-                            );
-                            let binop = BinaryOperation {
-                                left: Expr::Column(ColumnName {
-                                    name: local_complete_name,
-                                }),
-                                operator: Operator::Eq,
-                                right: Expr::Column(ColumnName {
-                                    name: referred_complete_name,
-                                }),
-                            };
-                            joins.push(binop);
-                        }
+    /// the column equalities derived from the foreign keys of this table
+    /// to the joined table, and of the joined table to this table
+    fn foreign_join_columns(
+        &self,
+        table_lookup: &TableLookup,
+        joined_table: &FromTable,
+    ) -> Result<Vec<BinaryOperation>, crate::Error> {
+        let this_table_def =
+            table_lookup.get_table_def(&self.from.name).ok_or_else(|| {
+                TableError::TableNotFound(self.from.name.to_string())
+            })?;
+        let joined_table_def = table_lookup
+            .get_table_def(&joined_table.from.name)
+            .ok_or_else(|| {
+                TableError::TableNotFound(joined_table.from.name.to_string())
+            })?;
 
-                        let mut ret = vec![];
-                        if !joins.is_empty() {
-                            let constraint =
-                                Self::combine_expressions(joins, Operator::And);
-                            ret.push(sql::Join {
-                                relation: Into::into(&joined_table.from),
-                                join_operator: join_type
-                                    .into_sql_join_operator(constraint),
-                            });
+        let pair1 = this_table_def
+            .get_local_foreign_columns_pair_to_table(joined_table_def);
+        // in a self join, the foreign keys of the joined table
+        // are the same as this table
+        let pair2 = if this_table_def.table == joined_table_def.table {
+            vec![]
+        } else {
+            joined_table_def
+                .get_local_foreign_columns_pair_to_table(this_table_def)
+        };
 
-                            dbg!(&ret);
+        let mut joins = vec![];
+        for (local_col, foreign_col) in pair1 {
+            joins.push(BinaryOperation {
+                left: Expr::Column(qualified_column(
+                    self.reference(),
+                    local_col,
+                )),
+                operator: Operator::Eq,
+                right: Expr::Column(qualified_column(
+                    joined_table.reference(),
+                    foreign_col,
+                )),
+            });
+        }
+        for (local_col, foreign_col) in pair2 {
+            joins.push(BinaryOperation {
+                left: Expr::Column(qualified_column(
+                    joined_table.reference(),
+                    local_col,
+                )),
+                operator: Operator::Eq,
+                right: Expr::Column(qualified_column(
+                    self.reference(),
+                    foreign_col,
+                )),
+            });
+        }
+        if joins.is_empty() {
+            Err(TableError::NoJoinCondition(
+                self.from.name.to_string(),
+                joined_table.from.name.to_string(),
+            )
+            .into())
+        } else {
+            Ok(joins)
+        }
+    }
+}
 
-                            let more_joins =
-                                joined_table.extract_join(table_lookup)?;
-                            println!("more joins: {:#?}", more_joins);
-                            ret.extend(more_joins);
-                        }
-                        Ok(ret)
-                    }
-                }
-            }
-            None => Ok(vec![]),
+/// qualify the column with the table reference, unless it is already qualified
+fn qualified_column(table: &str, column: &ColumnName) -> ColumnName {
+    if column.name.contains('.') {
+        column.clone()
+    } else {
+        ColumnName {
+            name: format!("{}.{}", table, column.name),
         }
     }
 }
@@ -257,6 +310,17 @@ impl FromTable {
 impl fmt::Display for FromTable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.from.fmt(f)?;
+        if let Some(alias) = &self.alias {
+            write!(f, "=>{}", alias)?;
+        }
+        for (i, (local_col, joined_col)) in self.on.iter().enumerate() {
+            if i == 0 {
+                write!(f, ":")?;
+            } else {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", local_col, joined_col)?;
+        }
         if let Some((join_type, from_table)) = &self.join {
             join_type.fmt(f)?;
             from_table.fmt(f)?;
@@ -275,6 +339,10 @@ impl fmt::Display for TableDef {
             }
             col.fmt(f)?;
         }
+        for constraint in self.constraints.iter() {
+            write!(f, ",")?;
+            constraint.fmt(f)?;
+        }
         write!(f, "}}")?;
         Ok(())
     }
@@ -1,22 +1,40 @@
-use crate::sql;
+use crate::{sql, Dialect};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{fmt, num::ParseFloatError};
 
 /// coarse value from the parsing
 /// this is close to the json values
-///
-/// TODO: include Integer variant
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Value {
     Null,
     String(String),
+    /// number in exponent notation, ie: `1e10`
     Number(f64),
+    /// number without fraction which fits in i64
+    Integer(i64),
+    /// number with fraction or an integer too large for i64,
+    /// kept as written so no precision is lost, ie: `12.50`
+    Decimal(String),
     Bool(bool),
 }
 
-/// if string is empty use the default value in the underlying database
-impl Into<sql::Value> for &Value {
-    fn into(self) -> sql::Value {
+impl Value {
+    /// the value of the number literal, without going through a float
+    /// unless it is written in exponent notation
+    pub fn from_number_literal(literal: &str) -> Result<Self, ParseFloatError> {
+        if literal.contains(['e', 'E']) {
+            Ok(Value::Number(literal.parse()?))
+        } else if literal.contains('.') {
+            Ok(Value::Decimal(literal.to_string()))
+        } else if let Ok(v) = literal.parse() {
+            Ok(Value::Integer(v))
+        } else {
+            Ok(Value::Decimal(literal.to_string()))
+        }
+    }
+
+    /// if string is empty use the default value in the underlying database
+    pub fn into_sql_value(&self, dialect: &Dialect) -> sql::Value {
         match self {
             Value::Null => sql::Value::Null,
             Value::String(v) => {
@@ -27,7 +45,9 @@ impl Into<sql::Value> for &Value {
                 }
             }
             Value::Number(v) => sql::Value::Number(format!("{}", v)),
-            Value::Bool(v) => sql::Value::Boolean(*v),
+            Value::Integer(v) => sql::Value::Number(v.to_string()),
+            Value::Decimal(v) => sql::Value::Number(v.to_string()),
+            Value::Bool(v) => dialect.bool_value(*v),
         }
     }
 }
@@ -38,6 +58,8 @@ impl fmt::Display for Value {
             Value::Null => write!(f, "null"),
             Value::String(v) => write!(f, "'{}'", v),
             Value::Number(v) => write!(f, "{}", v),
+            Value::Integer(v) => write!(f, "{}", v),
+            Value::Decimal(v) => write!(f, "{}", v),
             Value::Bool(v) => write!(f, "{}", v),
         }
     }
@@ -1,4 +1,4 @@
-use crate::{ast::parser::ident, Error};
+use crate::{ast::parser::ident, Dialect, Error};
 use pom::parser::*;
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
@@ -205,24 +205,21 @@ pub fn data_type<'a>() -> Parser<'a, char, DataType> {
     ident().convert(|v| DataType::match_data_type(&v))
 }
 
-/// the corresponding sql type for this data type
-impl Into<sql::DataType> for &DataType {
-    fn into(self) -> sql::DataType {
-        //TODO: serial is for postgresql only
-        let serial =
-            sql::DataType::Custom(sql::ObjectName(vec![sql::Ident::new(
-                "SERIAL",
-            )]));
-        let big_serial =
-            sql::DataType::Custom(sql::ObjectName(vec![sql::Ident::new(
-                "BIGSERIAL",
-            )]));
+impl DataType {
+    /// the corresponding sql type for this data type in the dialect
+    pub fn into_sql_data_type(&self, dialect: &Dialect) -> sql::DataType {
         match self {
             DataType::Bool => sql::DataType::Boolean,
-            DataType::S8 => serial,
-            DataType::S16 => serial,
-            DataType::S32 => serial,
-            DataType::S64 => big_serial,
+            DataType::S8 | DataType::S16 | DataType::S32 => match dialect {
+                Dialect::PostgreSql => custom_data_type("SERIAL"),
+                Dialect::Sqlite => custom_data_type("INTEGER"),
+                Dialect::MySql => sql::DataType::Int,
+            },
+            DataType::S64 => match dialect {
+                Dialect::PostgreSql => custom_data_type("BIGSERIAL"),
+                Dialect::Sqlite => custom_data_type("INTEGER"),
+                Dialect::MySql => sql::DataType::BigInt,
+            },
             DataType::F32 => sql::DataType::Float(None),
             DataType::F64 => sql::DataType::Float(None),
             DataType::U8 => sql::DataType::SmallInt,
@@ -233,18 +230,66 @@ impl Into<sql::DataType> for &DataType {
             DataType::I16 => sql::DataType::SmallInt,
             DataType::I32 => sql::DataType::Int,
             DataType::I64 => sql::DataType::BigInt,
-            DataType::Uuid => sql::DataType::Uuid,
-            DataType::UuidRand => sql::DataType::Uuid,
+            DataType::Uuid | DataType::UuidRand => match dialect {
+                Dialect::PostgreSql => sql::DataType::Uuid,
+                Dialect::Sqlite => sql::DataType::Text,
+                Dialect::MySql => sql::DataType::Char(Some(36)),
+            },
             DataType::UuidSlug => sql::DataType::Text,
             DataType::Local => sql::DataType::Timestamp,
             DataType::Utc => sql::DataType::Timestamp,
             DataType::Text => sql::DataType::Text,
             DataType::Ident => sql::DataType::Text,
             DataType::Url => sql::DataType::Text,
-            DataType::Json => sql::DataType::Json,
-            DataType::Bytes => sql::DataType::Bytea,
+            DataType::Json => match dialect {
+                Dialect::PostgreSql | Dialect::MySql => sql::DataType::Json,
+                Dialect::Sqlite => sql::DataType::Text,
+            },
+            DataType::Bytes => match dialect {
+                Dialect::PostgreSql => sql::DataType::Bytea,
+                Dialect::Sqlite => custom_data_type("BLOB"),
+                Dialect::MySql => custom_data_type("LONGBLOB"),
+            },
+        }
+    }
+
+    /// the length of the prefix that is indexed, when the database can only
+    /// index a prefix of this type, ie: `TEXT` and `BLOB` in mysql
+    pub(crate) fn index_prefix_length(&self, dialect: &Dialect) -> Option<u64> {
+        match (self, dialect) {
+            (
+                DataType::Text
+                | DataType::Ident
+                | DataType::Url
+                | DataType::UuidSlug
+                | DataType::Bytes,
+                Dialect::MySql,
+            ) => Some(255),
+            _ => None,
         }
     }
+
+    /// the sql type of a primary column, mysql can only auto increment
+    /// a key so the serial type of the other columns is a plain integer
+    pub(crate) fn primary_sql_data_type(
+        &self,
+        dialect: &Dialect,
+    ) -> sql::DataType {
+        match (self, dialect) {
+            (DataType::S8 | DataType::S16 | DataType::S32, Dialect::MySql) => {
+                custom_data_type("INT AUTO_INCREMENT")
+            }
+            (DataType::S64, Dialect::MySql) => {
+                custom_data_type("BIGINT AUTO_INCREMENT")
+            }
+            _ => self.into_sql_data_type(dialect),
+        }
+    }
+}
+
+/// a sql type which has no corresponding variant in sql-ast
+fn custom_data_type(name: &str) -> sql::DataType {
+    sql::DataType::Custom(sql::ObjectName(vec![sql::Ident::new(name)]))
 }
 
 #[cfg(test)]
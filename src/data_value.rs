@@ -1,6 +1,7 @@
 use crate::{
     ast::{Expr, Value},
     data_type::DataType,
+    Dialect, Error,
 };
 use chrono::{
     offset::FixedOffset, DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime,
@@ -8,7 +9,7 @@ use chrono::{
 };
 use serde::{Deserialize, Serialize};
 use sql_ast::ast as sql;
-use std::fmt;
+use std::{convert::TryFrom, fmt};
 use uuid::Uuid;
 
 /// strict data value
@@ -75,8 +76,8 @@ impl DataValue {
     }
 }
 
-fn naive_date_parser(v: &str) -> NaiveDateTime {
-    if let Ok(dt) = DateTime::parse_from_rfc3339(v) {
+fn naive_date_parser(v: &str) -> Result<NaiveDateTime, Error> {
+    let ts = if let Ok(dt) = DateTime::parse_from_rfc3339(v) {
         dt.naive_local()
     } else if let Ok(ts) =
         NaiveDateTime::parse_from_str(&v, "%Y-%m-%dT%H:%M:%S%z")
@@ -96,8 +97,12 @@ fn naive_date_parser(v: &str) -> NaiveDateTime {
             NaiveTime::from_hms_milli_opt(0, 0, 0, 0).unwrap(),
         )
     } else {
-        panic!("unable to parse timestamp: {:?}", v);
-    }
+        return Err(Error::UnsupportedConversion(
+            Value::String(v.to_string()),
+            DataType::Utc,
+        ));
+    };
+    Ok(ts)
 }
 
 impl Into<u32> for DataValue {
@@ -236,17 +241,10 @@ impl Into<Expr> for Value {
     }
 }
 
-impl Into<sql::Expr> for &Value {
-    fn into(self) -> sql::Expr {
-        let expr: Expr = Into::into(self.clone());
-        Into::into(&expr)
-    }
-}
-
-impl Into<sql::Value> for &DataValue {
-    fn into(self) -> sql::Value {
+impl DataValue {
+    pub fn into_sql_value(&self, dialect: &Dialect) -> sql::Value {
         match self {
-            DataValue::Bool(v) => sql::Value::Boolean(*v),
+            DataValue::Bool(v) => dialect.bool_value(*v),
             DataValue::U8(v) => sql::Value::Number(v.to_string()),
             DataValue::U16(v) => sql::Value::Number(v.to_string()),
             DataValue::U32(v) => sql::Value::Number(v.to_string()),
@@ -262,14 +260,17 @@ impl Into<Value> for &DataValue {
     fn into(self) -> Value {
         match self {
             DataValue::Bool(v) => Value::Bool(*v),
-            DataValue::U8(v) => Value::Number(*v as f64),
-            DataValue::U16(v) => Value::Number(*v as f64),
-            DataValue::U32(v) => Value::Number(*v as f64),
-            DataValue::U64(v) => Value::Number(*v as f64),
-            DataValue::I8(v) => Value::Number(*v as f64),
-            DataValue::I16(v) => Value::Number(*v as f64),
-            DataValue::I32(v) => Value::Number(*v as f64),
-            DataValue::I64(v) => Value::Number(*v as f64),
+            DataValue::U8(v) => Value::Integer(*v as i64),
+            DataValue::U16(v) => Value::Integer(*v as i64),
+            DataValue::U32(v) => Value::Integer(*v as i64),
+            DataValue::U64(v) => match i64::try_from(*v) {
+                Ok(v) => Value::Integer(v),
+                Err(_) => Value::Decimal(v.to_string()),
+            },
+            DataValue::I8(v) => Value::Integer(*v as i64),
+            DataValue::I16(v) => Value::Integer(*v as i64),
+            DataValue::I32(v) => Value::Integer(*v as i64),
+            DataValue::I64(v) => Value::Integer(*v),
             DataValue::F32(v) => Value::Number(*v as f64),
             DataValue::F64(v) => Value::Number(*v as f64),
             DataValue::Text(v) => Value::String(v.clone()),
@@ -279,15 +280,43 @@ impl Into<Value> for &DataValue {
     }
 }
 
-impl Into<sql::Expr> for &DataValue {
-    fn into(self) -> sql::Expr {
-        sql::Expr::Value(Into::into(self))
+/// the natural conversion of the coarse value when
+/// there is no data type to cast it into, a decimal is kept
+/// as text so it does not lose its precision in a float
+impl From<&Value> for DataValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => DataValue::Nil,
+            Value::Bool(v) => DataValue::Bool(*v),
+            Value::Number(v) => DataValue::F64(*v),
+            Value::Integer(v) => DataValue::I64(*v),
+            Value::Decimal(v) => match v.parse() {
+                Ok(v) => DataValue::U64(v),
+                Err(_) => DataValue::Text(v.to_string()),
+            },
+            Value::String(v) => DataValue::Text(v.to_string()),
+        }
     }
 }
 
 /// cast the value into DataValue hinted by the data_type
+///
+/// Note: this panics when the value can not be converted into the
+/// required type, use `try_cast_data_value` to get the error instead.
 pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
-    if *value == Value::Null {
+    match try_cast_data_value(value, required_type) {
+        Ok(data_value) => data_value,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// cast the value into DataValue hinted by the data_type,
+/// returns an error when the value can not be converted
+pub fn try_cast_data_value(
+    value: &Value,
+    required_type: &DataType,
+) -> Result<DataValue, Error> {
+    let data_value = if *value == Value::Null {
         DataValue::Nil
     } else {
         match *value {
@@ -306,10 +335,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                 DataType::S32 => DataValue::S32(if v { 1 } else { 0 }),
                 DataType::S64 => DataValue::S64(if v { 1 } else { 0 }),
                 _ => {
-                    panic!(
-                        "unsupported conversion from {:?} to {:?}",
-                        value, required_type
-                    )
+                    return Err(Error::UnsupportedConversion(
+                        value.clone(),
+                        required_type.clone(),
+                    ));
                 }
             },
             Value::Number(v) => match *required_type {
@@ -328,12 +357,52 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                 DataType::S32 => DataValue::S32(v as u32),
                 DataType::S64 => DataValue::S64(v as u64),
                 _ => {
-                    panic!(
-                        "unsupported conversion from {:?} to {:?}",
-                        value, required_type
-                    )
+                    return Err(Error::UnsupportedConversion(
+                        value.clone(),
+                        required_type.clone(),
+                    ));
                 }
             },
+            Value::Integer(v) => {
+                let data_value = match *required_type {
+                    DataType::U8 => u8::try_from(v).ok().map(DataValue::U8),
+                    DataType::U16 => u16::try_from(v).ok().map(DataValue::U16),
+                    DataType::U32 => u32::try_from(v).ok().map(DataValue::U32),
+                    DataType::U64 => u64::try_from(v).ok().map(DataValue::U64),
+                    DataType::I8 => i8::try_from(v).ok().map(DataValue::I8),
+                    DataType::I16 => i16::try_from(v).ok().map(DataValue::I16),
+                    DataType::I32 => i32::try_from(v).ok().map(DataValue::I32),
+                    DataType::I64 => Some(DataValue::I64(v)),
+                    DataType::F32 => Some(DataValue::F32(v as f32)),
+                    DataType::F64 => Some(DataValue::F64(v as f64)),
+                    DataType::S8 => u8::try_from(v).ok().map(DataValue::S8),
+                    DataType::S16 => u16::try_from(v).ok().map(DataValue::S16),
+                    DataType::S32 => u32::try_from(v).ok().map(DataValue::S32),
+                    DataType::S64 => u64::try_from(v).ok().map(DataValue::S64),
+                    _ => None,
+                };
+                data_value.ok_or_else(|| {
+                    Error::UnsupportedConversion(
+                        value.clone(),
+                        required_type.clone(),
+                    )
+                })?
+            }
+            Value::Decimal(ref v) => {
+                let data_value = match *required_type {
+                    DataType::U64 => v.parse().ok().map(DataValue::U64),
+                    DataType::S64 => v.parse().ok().map(DataValue::S64),
+                    DataType::F32 => v.parse().ok().map(DataValue::F32),
+                    DataType::F64 => v.parse().ok().map(DataValue::F64),
+                    _ => None,
+                };
+                data_value.ok_or_else(|| {
+                    Error::UnsupportedConversion(
+                        value.clone(),
+                        required_type.clone(),
+                    )
+                })?
+            }
             Value::String(ref v) => {
                 match *required_type {
                     DataType::Text => DataValue::Text(v.to_string()),
@@ -350,10 +419,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u8>() {
                             DataValue::S8(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::S16 => {
@@ -362,10 +431,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u16>() {
                             DataValue::S16(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::S32 => {
@@ -374,10 +443,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u32>() {
                             DataValue::S32(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::S64 => {
@@ -386,10 +455,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u64>() {
                             DataValue::S64(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::U8 => {
@@ -400,10 +469,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u8>() {
                             DataValue::U8(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::U16 => {
@@ -412,10 +481,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u16>() {
                             DataValue::U16(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::U32 => {
@@ -424,10 +493,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u32>() {
                             DataValue::U32(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::U64 => {
@@ -436,10 +505,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<u64>() {
                             DataValue::U64(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::I8 => {
@@ -448,10 +517,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<i8>() {
                             DataValue::I8(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::I16 => {
@@ -460,10 +529,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<i16>() {
                             DataValue::I16(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::I32 => {
@@ -472,10 +541,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<i32>() {
                             DataValue::I32(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::I64 => {
@@ -484,10 +553,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<i64>() {
                             DataValue::I64(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::F32 => {
@@ -496,10 +565,10 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<f32>() {
                             DataValue::F32(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::F64 => {
@@ -508,17 +577,17 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         } else if let Ok(v) = v.parse::<f64>() {
                             DataValue::F64(v)
                         } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
+                            return Err(Error::UnsupportedConversion(
+                                value.clone(),
+                                required_type.clone(),
+                            ));
                         }
                     }
                     DataType::Local => {
                         if v.is_empty() {
                             DataValue::Nil
                         } else {
-                            let ts = naive_date_parser(&v);
+                            let ts = naive_date_parser(&v)?;
                             let local = DateTime::<Local>::from_utc(
                                 ts,
                                 FixedOffset::east_opt(0).unwrap(),
@@ -530,7 +599,7 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                         if v.is_empty() {
                             DataValue::Nil
                         } else {
-                            let ts = naive_date_parser(&v);
+                            let ts = naive_date_parser(&v)?;
                             let utc = DateTime::<Utc>::from_utc(ts, Utc);
                             DataValue::Utc(utc)
                         }
@@ -546,26 +615,32 @@ pub fn cast_data_value(value: &Value, required_type: &DataType) -> DataValue {
                     DataType::Ident => DataValue::Ident(v.to_string()),
                     DataType::Bytes => {
                         let bytes = base64::decode_config(&v, base64::URL_SAFE)
-                            .expect("must be a valid base64 bytes");
+                            .map_err(|_| {
+                                Error::UnsupportedConversion(
+                                    value.clone(),
+                                    required_type.clone(),
+                                )
+                            })?;
                         DataValue::Bytes(bytes)
                     }
                     DataType::Json => DataValue::Text(v.to_string()),
                     _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
+                        return Err(Error::UnsupportedConversion(
+                            value.clone(),
+                            required_type.clone(),
+                        ));
                     }
                 }
             }
             _ => {
-                panic!(
-                    "unsupported conversion from {:?} to {:?}",
-                    value, required_type
-                )
+                return Err(Error::UnsupportedConversion(
+                    value.clone(),
+                    required_type.clone(),
+                ));
             }
         }
-    }
+    };
+    Ok(data_value)
 }
 
 impl fmt::Display for DataValue {
@@ -613,7 +688,7 @@ mod test {
         let parsed = NaiveDateTime::parse_from_str(date, "%Y-%m-%d");
         println!("parsed: {:?}", parsed);
 
-        let res = naive_date_parser(date);
+        let res = naive_date_parser(date).expect("must be parsed");
         let naive_date = NaiveDate::from_ymd_opt(2006, 2, 14).unwrap();
         let naive_time = NaiveTime::from_hms_milli_opt(0, 0, 0, 0).unwrap();
         assert_eq!(res, NaiveDateTime::new(naive_date, naive_time));
@@ -622,10 +697,49 @@ mod test {
     #[test]
     fn parse_dates() {
         let date = "2006-02-15T09:34:33+00:00";
-        let res = naive_date_parser(date);
+        let res = naive_date_parser(date).expect("must be parsed");
         println!("res: {}", res);
         let naive_date = NaiveDate::from_ymd_opt(2006, 2, 15).unwrap();
         let naive_time = NaiveTime::from_hms_milli_opt(9, 34, 33, 0).unwrap();
         assert_eq!(res, NaiveDateTime::new(naive_date, naive_time));
     }
+
+    #[test]
+    fn cast_integer_without_loss() {
+        let value = Value::Integer(9007199254740993);
+        assert_eq!(
+            cast_data_value(&value, &DataType::U64),
+            DataValue::U64(9007199254740993)
+        );
+        assert_eq!(
+            cast_data_value(&value, &DataType::I64),
+            DataValue::I64(9007199254740993)
+        );
+        assert!(try_cast_data_value(&value, &DataType::U8).is_err());
+        assert!(
+            try_cast_data_value(&Value::Integer(-1), &DataType::U32).is_err()
+        );
+    }
+
+    #[test]
+    fn cast_decimal_without_loss() {
+        let value = Value::Decimal("18446744073709551615".into());
+        assert_eq!(
+            cast_data_value(&value, &DataType::U64),
+            DataValue::U64(u64::MAX)
+        );
+        let value = Value::Decimal("12.50".into());
+        assert_eq!(value.to_string(), "12.50");
+        assert_eq!(
+            cast_data_value(&value, &DataType::F64),
+            DataValue::F64(12.5)
+        );
+        assert!(try_cast_data_value(&value, &DataType::I32).is_err());
+        assert_eq!(DataValue::from(&value), DataValue::Text("12.50".into()));
+        let value = Value::Decimal("123456789012345678901234567890.5".into());
+        assert_eq!(
+            DataValue::from(&value),
+            DataValue::Text("123456789012345678901234567890.5".into())
+        );
+    }
 }
@@ -0,0 +1,370 @@
+//! The database specific differences in the generated sql
+use serde::{Deserialize, Serialize};
+use sql_ast::ast as sql;
+
+/// The database the generated sql statements are intended for.
+///
+/// The restq AST is database agnostic, the dialect is used when converting
+/// it into sql to choose the syntax and types the database understands:
+///  - autoincrement columns: `SERIAL` in postgresql, `INTEGER` in sqlite
+///    which is an alias of the rowid, `AUTO_INCREMENT` in mysql
+///  - `ILIKE` is only supported in postgresql, it is emulated with
+///    `LOWER(expr) LIKE LOWER(pattern)` in the other databases
+///  - a `starts`, `ends` and `contains` value with wildcards is compared
+///    with `substr` and `instr`, which are `right` and `strpos` in postgresql
+///  - boolean literals are `1` and `0` in sqlite
+///  - uuid, json and bytes type maps to the closest native type
+///  - placeholder of bind values is `$1`, `$2`.. in postgresql, `?` in the
+///    other databases
+///  - nulls are sorted as the largest values in postgresql and as the
+///    smallest in sqlite and mysql, a different nulls ordering is emulated
+///    with `expr IS NULL`
+///  - a select in a parenthesis can not be combined with `UNION` in sqlite,
+///    it is selected from as a subquery instead
+///  - upsert is `ON CONFLICT .. DO UPDATE` in postgresql and sqlite,
+///    `ON DUPLICATE KEY UPDATE` in mysql
+///  - `RETURNING` is supported in postgresql and sqlite, an error in mysql
+///  - the unique and foreign keys are named constraints of the columns,
+///    mysql declares them in the table instead
+///  - `CREATE INDEX IF NOT EXISTS` is not supported in mysql, and its
+///    `DROP INDEX` needs the table
+///  - `DROP COLUMN IF EXISTS` is only supported in postgresql
+#[derive(
+    Debug, PartialEq, Clone, Copy, Hash, Eq, Default, Serialize, Deserialize,
+)]
+pub enum Dialect {
+    #[default]
+    PostgreSql,
+    Sqlite,
+    MySql,
+}
+
+impl Dialect {
+    /// true if the database supports the `ILIKE` operator natively
+    pub fn supports_ilike(&self) -> bool {
+        match self {
+            Dialect::PostgreSql => true,
+            Dialect::Sqlite | Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database sorts the nulls after the other values, that is
+    /// last in ascending order and first in descending order
+    pub fn nulls_are_largest(&self) -> bool {
+        match self {
+            Dialect::PostgreSql => true,
+            Dialect::Sqlite | Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports a query in a parenthesis as an operand
+    /// of `UNION`, `INTERSECT` and `EXCEPT`
+    pub fn supports_nested_set_operations(&self) -> bool {
+        match self {
+            Dialect::PostgreSql | Dialect::MySql => true,
+            Dialect::Sqlite => false,
+        }
+    }
+
+    /// true if the database supports `ON CONFLICT` in insert
+    pub fn supports_on_conflict(&self) -> bool {
+        match self {
+            Dialect::PostgreSql | Dialect::Sqlite => true,
+            Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports `RETURNING` in insert, update and delete
+    pub fn supports_returning(&self) -> bool {
+        match self {
+            Dialect::PostgreSql | Dialect::Sqlite => true,
+            Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports `IF NOT EXISTS` in create index
+    pub fn supports_index_if_not_exists(&self) -> bool {
+        match self {
+            Dialect::PostgreSql | Dialect::Sqlite => true,
+            Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports `IF EXISTS` in drop column
+    pub fn supports_drop_column_if_exists(&self) -> bool {
+        match self {
+            Dialect::PostgreSql => true,
+            Dialect::Sqlite | Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports naming a column constraint,
+    /// ie: `name text CONSTRAINT product_name_key UNIQUE`
+    pub fn supports_named_column_constraints(&self) -> bool {
+        match self {
+            Dialect::PostgreSql | Dialect::Sqlite => true,
+            Dialect::MySql => false,
+        }
+    }
+
+    /// true if the database supports `CASCADE` when dropping tables and columns
+    pub fn supports_cascade(&self) -> bool {
+        match self {
+            Dialect::PostgreSql => true,
+            Dialect::Sqlite | Dialect::MySql => false,
+        }
+    }
+
+    /// the sql literal for the boolean value
+    pub fn bool_value(&self, v: bool) -> sql::Value {
+        match self {
+            Dialect::PostgreSql | Dialect::MySql => sql::Value::Boolean(v),
+            Dialect::Sqlite => {
+                sql::Value::Number(if v { "1" } else { "0" }.to_string())
+            }
+        }
+    }
+
+    /// true if `substr` counts a negative start from the end of the string
+    pub fn supports_negative_substr(&self) -> bool {
+        match self {
+            Dialect::Sqlite | Dialect::MySql => true,
+            Dialect::PostgreSql => false,
+        }
+    }
+
+    /// the function which returns the 1-based position of a substring
+    /// in a string, 0 if not found
+    pub fn position_function(&self) -> &'static str {
+        match self {
+            Dialect::PostgreSql => "strpos",
+            Dialect::Sqlite | Dialect::MySql => "instr",
+        }
+    }
+
+    /// the placeholder for the bind value at this 1-based index
+    pub fn placeholder(&self, index: usize) -> String {
+        match self {
+            Dialect::PostgreSql => format!("${}", index),
+            Dialect::Sqlite | Dialect::MySql => "?".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::{
+            ddl::{drop_table, table_def},
+            Statement,
+        },
+        parser::select,
+        to_chars, DataValue,
+    };
+
+    fn create_product(dialect: &Dialect) -> String {
+        let input = to_chars(
+            "product{*product_id:s32,name:text,is_active:bool(true),owner_id:uuid,info:json?,image:bytes?}",
+        );
+        let table_def = table_def().parse(&input).expect("must be parsed");
+        table_def
+            .into_sql_statement(None, dialect)
+            .expect("must not fail")
+            .to_string()
+    }
+
+    #[test]
+    fn create_table_in_each_dialect() {
+        assert_eq!(
+            create_product(&Dialect::PostgreSql),
+            "CREATE TABLE IF NOT EXISTS product (product_id SERIAL PRIMARY KEY NOT NULL, name text NOT NULL, is_active boolean NOT NULL DEFAULT true, owner_id uuid NOT NULL, info json, image bytea)"
+        );
+        assert_eq!(
+            create_product(&Dialect::Sqlite),
+            "CREATE TABLE IF NOT EXISTS product (product_id INTEGER PRIMARY KEY NOT NULL, name text NOT NULL, is_active boolean NOT NULL DEFAULT 1, owner_id text NOT NULL, info text, image BLOB)"
+        );
+        assert_eq!(
+            create_product(&Dialect::MySql),
+            "CREATE TABLE IF NOT EXISTS product (product_id INT AUTO_INCREMENT PRIMARY KEY NOT NULL, name text NOT NULL, is_active boolean NOT NULL DEFAULT true, owner_id char(36) NOT NULL, info json, image LONGBLOB)"
+        );
+    }
+
+    #[test]
+    fn only_primary_serial_is_auto_increment_in_mysql() {
+        let input = to_chars("ticket{*ticket_id:s64,seq:s32}");
+        let table_def = table_def().parse(&input).expect("must be parsed");
+        let sql = |dialect| {
+            table_def
+                .into_sql_statement(None, dialect)
+                .expect("must not fail")
+                .to_string()
+        };
+        assert_eq!(
+            sql(&Dialect::MySql),
+            "CREATE TABLE IF NOT EXISTS ticket (ticket_id BIGINT AUTO_INCREMENT PRIMARY KEY NOT NULL, seq int NOT NULL)"
+        );
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "CREATE TABLE IF NOT EXISTS ticket (ticket_id BIGSERIAL PRIMARY KEY NOT NULL, seq SERIAL NOT NULL)"
+        );
+    }
+
+    #[test]
+    fn ilike_is_emulated() {
+        let input = to_chars("product?name=ilike.'%shovel%'&is_active=eq.true");
+        let select = select().parse(&input).expect("must be parsed");
+        let statement: Statement = select.into();
+        let sql = |dialect| {
+            statement
+                .into_sql_statement(None, dialect)
+                .expect("must not fail")
+                .to_string()
+        };
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "SELECT * FROM product WHERE name ILIKE '%shovel%' AND is_active = true"
+        );
+        assert_eq!(
+            sql(&Dialect::Sqlite),
+            "SELECT * FROM product WHERE LOWER(name) LIKE LOWER('%shovel%') AND is_active = 1"
+        );
+        assert_eq!(
+            sql(&Dialect::MySql),
+            "SELECT * FROM product WHERE LOWER(name) LIKE LOWER('%shovel%') AND is_active = true"
+        );
+    }
+
+    #[test]
+    fn nulls_ordering_is_emulated() {
+        let input = to_chars("product&order_by=price.desc.nullslast,name");
+        let select = select().parse(&input).expect("must be parsed");
+        assert_eq!(
+            select.to_string(),
+            "product&order_by=price.desc.nullslast,name"
+        );
+        let statement: Statement = select.into();
+        let sql =
+            |dialect| statement.into_sql(None, dialect).expect("must not fail");
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "SELECT * FROM product ORDER BY price IS NULL, price DESC, name"
+        );
+        assert_eq!(
+            sql(&Dialect::Sqlite),
+            "SELECT * FROM product ORDER BY price DESC, name"
+        );
+        assert_eq!(
+            sql(&Dialect::MySql),
+            "SELECT * FROM product ORDER BY price DESC, name"
+        );
+    }
+
+    #[test]
+    fn nulls_ordering_of_the_database_is_not_emulated() {
+        let input = to_chars("product&order_by=price.nullslast");
+        let statement: Statement =
+            select().parse(&input).expect("must be parsed").into();
+        let sql =
+            |dialect| statement.into_sql(None, dialect).expect("must not fail");
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "SELECT * FROM product ORDER BY price"
+        );
+        assert_eq!(
+            sql(&Dialect::Sqlite),
+            "SELECT * FROM product ORDER BY price IS NULL, price"
+        );
+    }
+
+    #[test]
+    fn nulls_ordering_is_parameterized() {
+        let input =
+            to_chars("product?price=gt.10&order_by=price*2.nullsfirst&limit=5");
+        let statement: Statement =
+            select().parse(&input).expect("must be parsed").into();
+        let (sql, values) = statement
+            .into_sql_with_params(None, &Dialect::PostgreSql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "SELECT * FROM product WHERE price > $1 ORDER BY price * $2 IS NOT NULL, price * $3 LIMIT 5"
+        );
+        assert_eq!(values.len(), 3);
+        let (sql, values) = statement
+            .into_sql_with_params(None, &Dialect::MySql)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "SELECT * FROM product WHERE price > ? ORDER BY price * ? LIMIT 5"
+        );
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn drop_table_cascade_only_in_postgresql() {
+        let input = to_chars("-product");
+        let drop_table = drop_table().parse(&input).expect("must be parsed");
+        assert_eq!(
+            drop_table
+                .into_sql_statement(&Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "DROP TABLE IF EXISTS product CASCADE"
+        );
+        assert_eq!(
+            drop_table
+                .into_sql_statement(&Dialect::Sqlite)
+                .unwrap()
+                .to_string(),
+            "DROP TABLE IF EXISTS product"
+        );
+    }
+
+    #[test]
+    fn value_with_wildcards_is_matched_literally() {
+        let input = to_chars("product?name=contains.'50%_off'&sku=ends.a_b");
+        let statement: Statement =
+            select().parse(&input).expect("must be parsed").into();
+        let sql =
+            |dialect| statement.into_sql(None, dialect).expect("must not fail");
+        assert_eq!(
+            sql(&Dialect::PostgreSql),
+            "SELECT * FROM product WHERE strpos(LOWER(name), LOWER('50%_off')) > 0 AND LOWER(right(sku, 3)) = LOWER('a_b')"
+        );
+        assert_eq!(
+            sql(&Dialect::Sqlite),
+            "SELECT * FROM product WHERE instr(LOWER(name), LOWER('50%_off')) > 0 AND LOWER(substr(sku, -3)) = LOWER('a_b')"
+        );
+        assert_eq!(sql(&Dialect::MySql), sql(&Dialect::Sqlite),);
+        // the sql text is the same with the bind values
+        let (sql, values) = statement
+            .into_sql_with_params(None, &Dialect::Sqlite)
+            .expect("must not fail");
+        assert_eq!(
+            sql,
+            "SELECT * FROM product WHERE instr(LOWER(name), LOWER(?)) > ? AND LOWER(substr(sku, ?)) = LOWER(?)"
+        );
+        assert_eq!(
+            values,
+            vec![
+                DataValue::Text("50%_off".into()),
+                DataValue::I64(0),
+                DataValue::I64(-3),
+                DataValue::Text("a_b".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        let input = to_chars("product?price=gt.100&name=eq.'shovel'");
+        let select = select().parse(&input).expect("must be parsed");
+        let statement: Statement = select.into();
+        let (sql, values) = statement
+            .into_sql_with_params(None, &Dialect::MySql)
+            .expect("must not fail");
+        assert_eq!(sql, "SELECT * FROM product WHERE price > ? AND name = ?");
+        assert_eq!(values.len(), 2);
+    }
+}
@@ -4,6 +4,7 @@ pub mod ast;
 mod csv_rows;
 mod data_type;
 pub mod data_value;
+mod dialect;
 pub mod multi_stmt;
 pub mod plain_data;
 pub mod stmt_data;
@@ -14,15 +15,17 @@ pub use ast::{
     ddl::{table_def, ColumnDef, Foreign},
     parser,
     parser::{
-        filter_expr, select,
-        utils::{bytes_to_chars, space, to_chars},
+        diagnostic::{Clause, Diagnostic},
+        filter_expr, select, set_operation,
+        utils::{bytes_to_chars, parse_diagnosed, space, to_chars},
     },
-    ColumnName, Expr, Operator, Select, TableDef, TableError, TableName,
+    ColumnName, Expr, Operator, Select, TableDef, TableError, TableName, Value,
 };
 pub use chrono;
 pub use csv_rows::CsvRows;
 pub use data_type::DataType;
 pub use data_value::DataValue;
+pub use dialect::Dialect;
 pub use multi_stmt::MultiStatement;
 pub use plain_data::PlainData;
 pub use pom;
@@ -34,14 +37,24 @@ pub use uuid::Uuid;
 pub enum Error {
     #[error("ParseError: {0}")]
     ParseError(#[from] pom::Error),
+    #[error("SyntaxError: {0}")]
+    SyntaxError(Diagnostic),
     #[error("Invalid DataType: {0}")]
     InvalidDataType(String),
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+    #[error("Unsupported conversion of {0:?} to {1:?}")]
+    UnsupportedConversion(Value, DataType),
     #[error("{0}")]
     TableError(#[from] TableError),
     #[error("GenericError: {0}")]
     GenericError(String),
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
     #[error("More than 1 statement is generated")]
     MoreThanOneStatement,
+    #[error("No statement is generated")]
+    NoStatement,
     #[error("{0}")]
     IoError(#[from] std::io::Error),
 }
@@ -3,7 +3,7 @@
 use crate::{
     ast::{
         ddl::{table_def, TableDef},
-        parser::utils::bytes_to_chars,
+        parser::utils::{bytes_to_chars, parse_diagnosed},
     },
     CsvRows,
 };
@@ -28,7 +28,8 @@ where
         let _header_len = bufread.read_until(b'\n', &mut first_line)?;
 
         let header_input = bytes_to_chars(&first_line);
-        let table_def = table_def().parse(&header_input)?;
+        let table_def = parse_diagnosed(table_def(), &header_input)
+            .map_err(crate::Error::SyntaxError)?;
 
         Ok(PlainData {
             header: table_def,
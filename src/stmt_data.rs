@@ -41,10 +41,13 @@ where
     pub fn rows_iter(self) -> Option<CsvRows<R>> {
         match self.header {
             Statement::Select(_) => None,
+            Statement::SetOperation(_) => None,
             Statement::Delete(_) => None,
             Statement::AlterTable(_) => None,
             Statement::DropTable(_) => None,
             Statement::Update(_) => None,
+            Statement::DescribeTable(_) => None,
+            Statement::ListTables => None,
             Statement::Create(_) => Some(CsvRows::new(self.body)),
             Statement::Insert(_) => Some(CsvRows::new(self.body)),
             Statement::BulkDelete(_) => Some(CsvRows::new(self.body)),
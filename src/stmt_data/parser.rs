@@ -1,11 +1,14 @@
 use crate::{
     ast::{
         ddl::{alter_table, drop_table, table_def},
-        dml::{bulk_delete, bulk_update, delete, insert, update},
-        parser::{utils::space, *},
+        dml::{bulk_delete, bulk_update, delete, insert, update, upsert},
+        parser::{
+            utils::{end_or_ln, farthest, parse_diagnosed, space},
+            *,
+        },
         Select, Statement,
     },
-    to_chars,
+    to_chars, Error,
 };
 use pom::parser::*;
 pub enum Prefix {
@@ -14,6 +17,7 @@ pub enum Prefix {
     Post,
     Patch,
     Delete,
+    Head,
 }
 
 pub fn parse_header(url: &str) -> Result<Statement, crate::Error> {
@@ -24,38 +28,50 @@ pub fn parse_header(url: &str) -> Result<Statement, crate::Error> {
 pub(crate) fn parse_statement_chars(
     input: &[char],
 ) -> Result<Statement, crate::Error> {
-    Ok(statement_with_prefix().parse(input)?)
+    parse_diagnosed(statement_with_prefix(), input).map_err(Error::SyntaxError)
 }
 
 /// parses a typical http url into a select statement
 pub fn parse_select_chars(input: &[char]) -> Result<Select, crate::Error> {
     let url_parser = sym('/') * select();
-    Ok(url_parser.parse(input)?)
+    parse_diagnosed(url_parser, input).map_err(Error::SyntaxError)
 }
 
 fn statement_with_prefix<'a>() -> Parser<'a, char, Statement> {
     (post_prefix() - space().opt() - sym('/'))
         * insert().map(Statement::Insert).expect("insert after POST")
-        | (put_prefix() - space().opt() - sym('/') - sym('+').opt())
-            * table_def()
-                .map(Statement::Create)
-                .expect("create after PUT")
+        | (put_prefix() - space().opt() - sym('/'))
+            * farthest(vec![
+                sym('+') * table_def().map(Statement::Create),
+                table_def().map(Statement::Create),
+                upsert().map(Statement::Insert),
+            ])
+            .expect("create or upsert after PUT")
         | (delete_prefix() - space().opt() - sym('/'))
             * (drop_table().map(Statement::DropTable)
                 | bulk_delete().map(Statement::BulkDelete)
                 | delete().map(Statement::Delete))
             .expect("drop table or delete after DELETE")
         | (patch_prefix() - space().opt() - sym('/'))
-            * (alter_table().map(Statement::AlterTable)
-                | update().map(Statement::Update)
-                | bulk_update().map(Statement::BulkUpdate))
+            * farthest(vec![
+                alter_table().map(Statement::AlterTable),
+                update().map(Statement::Update),
+                bulk_update().map(Statement::BulkUpdate),
+            ])
             .expect("alter or update after PATCH")
         | (get_prefix() - space().opt() - sym('/'))
-            * select().map(Statement::Select).expect("a select after GET")
+            * (set_operation().map(Statement::SetOperation)
+                | select().map(Statement::Select))
+            .expect("a select after GET")
+        | (head_prefix() - space().opt() - sym('/'))
+            * (table().map(Statement::DescribeTable)
+                | empty().map(|_| Statement::ListTables))
+            .expect("a table name or nothing after HEAD")
+            - end_or_ln()
         | sym('/')
-            * select()
-                .map(Statement::Select)
-                .expect("only select is allowed for no prefix")
+            * (set_operation().map(Statement::SetOperation)
+                | select().map(Statement::Select))
+            .expect("only select is allowed for no prefix")
 }
 
 fn post_prefix<'a>() -> Parser<'a, char, Prefix> {
@@ -78,6 +94,10 @@ fn put_prefix<'a>() -> Parser<'a, char, Prefix> {
     tag("PUT").map(|_| Prefix::Put)
 }
 
+fn head_prefix<'a>() -> Parser<'a, char, Prefix> {
+    tag("HEAD").map(|_| Prefix::Head)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,4 +290,160 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_syntax_error_diagnostic() {
+        let err = parse_header("GET /person{name,age?age=gt.42")
+            .expect_err("must fail");
+        match err {
+            Error::SyntaxError(diagnostic) => {
+                assert_eq!(diagnostic.position, 20);
+                assert_eq!(diagnostic.clause, Some(crate::Clause::Projection));
+                assert_eq!(diagnostic.found(), Some('?'));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_syntax_error_in_put_and_patch() {
+        let diagnose = |input| match parse_header(input) {
+            Err(Error::SyntaxError(diagnostic)) => diagnostic,
+            ret => panic!("must be a syntax error: {:?}", ret),
+        };
+        let diagnostic = diagnose("PUT /+product{*product_id:s32,name text}");
+        assert_eq!(diagnostic.clause, Some(crate::Clause::ColumnDef));
+        assert_eq!(diagnostic.position, 34);
+
+        let diagnostic = diagnose("PUT /product{*product_id:s32,name text}");
+        assert_eq!(diagnostic.clause, Some(crate::Clause::ColumnDef));
+        assert_eq!(diagnostic.position, 33);
+
+        let diagnostic = diagnose("PATCH /product{name='shovel',price}");
+        assert_eq!(diagnostic.clause, Some(crate::Clause::ColumnValues));
+
+        let diagnostic = diagnose("PATCH /product{name='shovel'}?(price=gt.1");
+        assert_eq!(diagnostic.clause, Some(crate::Clause::Filter));
+    }
+
+    #[test]
+    fn test_syntax_error_outside_a_clause() {
+        let _ = parse_header("GET /person{name,age?age=gt.42");
+        let err = parse_header("FETCH /person{name}").expect_err("must fail");
+        match err {
+            Error::SyntaxError(diagnostic) => {
+                assert_eq!(diagnostic.position, 0);
+                assert_eq!(diagnostic.clause, None);
+                assert!(diagnostic.expected.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_set_operation() {
+        let statement = parse_header(
+            "GET /(GET/product{name})union_all(GET/service{name})&order_by=name",
+        )
+        .expect("must be parsed");
+        assert_eq!(
+            statement
+                .into_sql_statement(None, &crate::Dialect::PostgreSql)
+                .unwrap()
+                .to_string(),
+            "SELECT name FROM product UNION ALL SELECT name FROM service ORDER BY name"
+        );
+    }
+
+    #[test]
+    fn test_head_describe_and_list_tables() {
+        let mut table_lookup = crate::ast::TableLookup::new();
+        for input in [
+            "users{*user_id:s32,name:text}",
+            "product{*product_id:s32,name:text,created_by(users):i32,price:f64?}",
+        ] {
+            let input = to_chars(input);
+            table_lookup.add_table(table_def().parse(&input).unwrap());
+        }
+
+        let describe = parse_header("HEAD /product").expect("must be parsed");
+        assert_eq!(
+            describe,
+            Statement::DescribeTable(crate::TableName {
+                name: "product".into()
+            })
+        );
+        let text = describe.describe(&table_lookup).unwrap();
+        assert_eq!(
+            text,
+            "product{*product_id:s32,name:text,created_by(users):i32,price:f64?}"
+        );
+        let text = to_chars(&text);
+        assert_eq!(
+            &table_def().parse(&text).unwrap(),
+            table_lookup.get_table_def("product").unwrap()
+        );
+
+        let list = parse_header("HEAD /").expect("must be parsed");
+        assert_eq!(list, Statement::ListTables);
+        assert_eq!(
+            list.describe(&table_lookup).unwrap(),
+            "product{*product_id:s32,name:text,created_by(users):i32,price:f64?}\n\
+            users{*user_id:s32,name:text}"
+        );
+
+        let missing = parse_header("HEAD /service").expect("must be parsed");
+        assert!(matches!(
+            missing.describe(&table_lookup),
+            Err(Error::TableError(crate::TableError::TableNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_head_is_not_converted_to_sql() {
+        let describe = parse_header("HEAD /product").expect("must be parsed");
+        assert!(matches!(
+            describe.into_sql(None, &crate::Dialect::PostgreSql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        let list = parse_header("HEAD /").expect("must be parsed");
+        assert!(matches!(
+            list.into_sql_with_params(None, &crate::Dialect::MySql),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        let select = parse_header("GET /product").expect("must be parsed");
+        assert!(matches!(
+            select.describe(&crate::ast::TableLookup::new()),
+            Err(Error::UnsupportedOperation(_))
+        ));
+        assert!(matches!(
+            parse_header("HEAD /product{name}"),
+            Err(Error::SyntaxError(_))
+        ));
+    }
+
+    #[test]
+    fn test_put_is_create_or_upsert() {
+        let create =
+            parse_header("PUT /product{*product_id:s32,name:text}").unwrap();
+        assert!(matches!(create, Statement::Create(_)));
+
+        let upsert = parse_header("PUT /product{product_id,name}").unwrap();
+        match upsert {
+            Statement::Insert(insert) => {
+                assert_eq!(insert.on_conflict, Some(vec![]))
+            }
+            _ => unreachable!(),
+        }
+
+        let upsert = parse_header("PUT /product{sku,name}?on_conflict=sku")
+            .expect("must be parsed");
+        match upsert {
+            Statement::Insert(insert) => assert_eq!(
+                insert.on_conflict,
+                Some(vec![crate::ColumnName { name: "sku".into() }])
+            ),
+            _ => unreachable!(),
+        }
+    }
 }
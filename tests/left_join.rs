@@ -52,6 +52,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -79,6 +81,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -168,6 +172,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "uuid_generate_v4".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -335,6 +341,7 @@ fn multiple_left_joins() {
                 }),
             },
         ],
+        constraints: vec![],
     };
 
     let product_review = TableDef {
@@ -377,6 +384,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -404,6 +413,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -471,6 +482,7 @@ fn multiple_left_joins() {
                 }),
             },
         ],
+        constraints: vec![],
     };
 
     let review = TableDef {
@@ -513,6 +525,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -540,6 +554,8 @@ fn multiple_left_joins() {
                     default: Some(DefaultValue::Function(Function {
                         name: "now".into(),
                         params: vec![],
+                        distinct: false,
+                        over: None,
                     })),
                 },
                 foreign: None,
@@ -698,13 +714,14 @@ fn multiple_left_joins() {
                 foreign: None,
             },
         ],
+        constraints: vec![],
     };
     table_lookup.add_table(product);
     table_lookup.add_table(product_review);
     table_lookup.add_table(review);
 
     let sql = ret
-        .into_sql_statement(Some(&table_lookup))
+        .into_sql_statement(Some(&table_lookup), &Dialect::PostgreSql)
         .expect("must not error");
     println!("sql: {}", sql.to_string());
     assert_eq!(input, ret.to_string());
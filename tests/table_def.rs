@@ -212,6 +212,7 @@ fn complex_table_def() {
                     foreign: None,
                 },
             ],
+            constraints: vec![],
         }
     )
 }
@@ -241,6 +242,8 @@ fn table_def_with_default_function() {
                             Function {
                                 name: "uuid_generate_v4".into(),
                                 params: vec![],
+                                distinct: false,
+                                over: None,
                             }
                             .into()
                         ),
@@ -270,7 +273,9 @@ fn table_def_with_default_function() {
                         default: Some(
                             Function {
                                 name: "now".to_string(),
-                                params: vec![]
+                                params: vec![],
+                                distinct: false,
+                                over: None,
                             }
                             .into()
                         ),
@@ -278,6 +283,7 @@ fn table_def_with_default_function() {
                     foreign: None,
                 },
             ],
+            constraints: vec![],
         }
     )
 }
@@ -307,6 +313,8 @@ fn table_def_has_column_with_space() {
                             Function {
                                 name: "uuid_generate_v4".into(),
                                 params: vec![],
+                                distinct: false,
+                                over: None,
                             }
                             .into()
                         ),
@@ -348,7 +356,9 @@ fn table_def_has_column_with_space() {
                         default: Some(
                             Function {
                                 name: "now".to_string(),
-                                params: vec![]
+                                params: vec![],
+                                distinct: false,
+                                over: None,
                             }
                             .into()
                         ),
@@ -356,6 +366,7 @@ fn table_def_has_column_with_space() {
                     foreign: None,
                 },
             ],
+            constraints: vec![],
         }
     )
 }